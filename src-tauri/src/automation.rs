@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::capture::capture_screen;
+use crate::commands::shortcuts::run_action;
+use crate::config;
+
+/// Longest request head (request line plus headers) the server will read
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+/// How long a client gets to send its request before the connection is dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The running automation server, if any
+#[derive(Default)]
+pub struct AutomationServerState {
+    addr: Mutex<Option<SocketAddr>>,
+    shutdown: AtomicBool,
+}
+
+/// Start the automation server when the config enables it
+///
+/// Binds to 127.0.0.1 only and requires the configured token on every request, so it
+/// refuses to start without one.
+pub fn start_automation_server(app: &AppHandle) {
+    let settings = config::load_config(app).automation;
+    if !settings.enabled {
+        return;
+    }
+    let Some(token) = settings.token.filter(|token| !token.is_empty()) else {
+        eprintln!("Automation server is enabled but has no token; not starting it");
+        return;
+    };
+
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!(
+                "Failed to start automation server on port {}: {}",
+                settings.port, e
+            );
+            return;
+        }
+    };
+    let state = app.state::<AutomationServerState>();
+    *state.addr.lock().unwrap() = listener.local_addr().ok();
+    state.shutdown.store(false, Ordering::SeqCst);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if app
+                .state::<AutomationServerState>()
+                .shutdown
+                .load(Ordering::SeqCst)
+            {
+                break;
+            }
+            let Ok(stream) = stream else {
+                continue;
+            };
+            // One request per connection, handled inline; automation calls are rare
+            if let Err(e) = handle_connection(&app, stream, &token) {
+                eprintln!("Automation request failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Stop the automation server, if it's running
+pub fn stop_automation_server(app: &AppHandle) {
+    let state = app.state::<AutomationServerState>();
+    let Some(addr) = state.addr.lock().unwrap().take() else {
+        return;
+    };
+    state.shutdown.store(true, Ordering::SeqCst);
+    // Wake the blocking accept so the server thread sees the flag and exits
+    let _ = TcpStream::connect_timeout(&addr, Duration::from_millis(200));
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream, token: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut authorized = false;
+    let mut head_len = request_line.len();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)?;
+        head_len += read;
+        if read == 0 || head_len > MAX_HEAD_BYTES {
+            return respond_error(stream, 400, "Bad request");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("authorization") {
+                authorized = value
+                    .trim()
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|given| tokens_match(given.trim(), token));
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return respond_error(stream, 400, "Bad request");
+    };
+    if !authorized {
+        return respond_error(stream, 401, "Unauthorized");
+    }
+    if method != "POST" {
+        return respond_error(stream, 405, "Use POST");
+    }
+
+    match path {
+        "/toggle" => match run_action(app, "toggle") {
+            Ok(()) => respond(stream, 200, &serde_json::json!({ "ok": true })),
+            Err(e) => respond_error(stream, 500, &e),
+        },
+        "/capture" => match capture_screen(app.clone(), None) {
+            Ok(capture) => respond(stream, 200, &capture),
+            Err(e) => respond_error(stream, 500, &e),
+        },
+        _ => match path.strip_prefix("/shortcut/") {
+            Some(action) => match run_action(app, action) {
+                Ok(()) => respond(stream, 200, &serde_json::json!({ "ok": true })),
+                Err(e) => respond_error(stream, 400, &e),
+            },
+            None => respond_error(stream, 404, "Not found"),
+        },
+    }
+}
+
+/// Compare tokens without bailing out at the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn respond_error(stream: TcpStream, status: u16, error: &str) -> std::io::Result<()> {
+    respond(stream, status, &serde_json::json!({ "error": error }))
+}
+
+fn respond<T: Serialize>(mut stream: TcpStream, status: u16, body: &T) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = serde_json::to_string(body).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
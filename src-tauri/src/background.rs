@@ -0,0 +1,86 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::capture::stream::CaptureStreamState;
+use crate::capture::watch::{self, ScreenWatchState};
+use crate::commands::{hot_corner, idle};
+use crate::power;
+
+/// What was running when pause_background was called, so resume can bring it back
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PausedTasks {
+    /// interval_ms and threshold of the screen watcher
+    pub screen_watch: Option<(u64, f64)>,
+    pub keep_awake: bool,
+    /// Capture streams are stopped for good, not resumed
+    pub capture_stream_stopped: bool,
+}
+
+/// Set while background activity is paused
+#[derive(Default)]
+pub struct BackgroundState(Mutex<Option<PausedTasks>>);
+
+/// Whether pause_background is in effect, for pollers that can't be stopped outright
+pub fn is_paused(app: &AppHandle) -> bool {
+    app.try_state::<BackgroundState>()
+        .is_some_and(|state| state.0.lock().unwrap().is_some())
+}
+
+/// Stop the screen watcher, any capture stream, the idle and hot corner timers and the
+/// screen recorder check, and release keep-awake
+///
+/// Emits `background-paused` with what was stopped. A capture stream is a one-off with a
+/// fixed duration, so it ends for good rather than being resumed.
+#[tauri::command]
+pub fn pause_background(
+    app: AppHandle,
+    state: State<'_, BackgroundState>,
+) -> Result<PausedTasks, String> {
+    let mut paused = state.0.lock().unwrap();
+    if paused.is_some() {
+        return Err("Background activity is already paused".to_string());
+    }
+
+    let tasks = PausedTasks {
+        screen_watch: watch::pause_screen_watch(&app),
+        keep_awake: power::pause_keep_awake(&app),
+        capture_stream_stopped: app.state::<CaptureStreamState>().stop(),
+    };
+    idle::pause_idle_watcher(&app);
+    hot_corner::stop_hot_corner_watcher(&app);
+
+    *paused = Some(tasks.clone());
+    let _ = app.emit("background-paused", &tasks);
+    Ok(tasks)
+}
+
+/// Restart whatever pause_background stopped, emitting `background-resumed`
+#[tauri::command]
+pub fn resume_background(app: AppHandle, state: State<'_, BackgroundState>) -> Result<(), String> {
+    let tasks = state
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or("Background activity isn't paused")?;
+
+    if let Some((interval_ms, threshold)) = tasks.screen_watch {
+        let watch_state = app.state::<ScreenWatchState>();
+        if let Err(e) = watch::start_screen_watch(app.clone(), watch_state, interval_ms, threshold)
+        {
+            eprintln!("Failed to resume the screen watcher: {}", e);
+        }
+    }
+    if tasks.keep_awake {
+        let keep_awake = app.state::<power::KeepAwakeState>();
+        if let Err(e) = power::set_keep_awake(keep_awake, true) {
+            eprintln!("Failed to resume keep awake: {}", e);
+        }
+    }
+    idle::resume_idle_watcher(&app);
+    hot_corner::start_hot_corner_watcher(&app);
+
+    let _ = app.emit("background-resumed", ());
+    Ok(())
+}
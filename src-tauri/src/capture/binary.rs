@@ -0,0 +1,165 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime, State, UriSchemeContext};
+
+use super::{
+    capture_raw, encode_png, platform, CaptureFormat, CaptureMetadata, PngCompression, RawCapture,
+};
+
+/// URI scheme the stored captures are served from
+pub const CAPTURE_SCHEME: &str = "lens-capture";
+/// How many binary captures are kept for the webview to load
+const MAX_STORED: usize = 8;
+
+/// Encoded captures from capture_screen_binary, oldest first
+#[derive(Default)]
+pub struct BinaryCaptureState {
+    stored: Mutex<VecDeque<StoredCapture>>,
+}
+
+struct StoredCapture {
+    id: String,
+    mime_type: String,
+    bytes: Vec<u8>,
+}
+
+/// A capture served over the lens-capture protocol instead of inlined as base64
+#[derive(Debug, Serialize, Clone)]
+pub struct BinaryCapture {
+    pub id: String,
+    /// Load this as an image src; the form differs by platform
+    pub url: String,
+    pub metadata: CaptureMetadata,
+}
+
+/// Capture the primary screen and hand back a `lens-capture://` URL for the image
+///
+/// The image bytes skip the IPC response entirely, so there's no base64 overhead or JSON
+/// string copy on the way to the webview. Only the last few captures are kept, after which
+/// their URLs stop resolving. capture_screen stays for callers that want the data inline.
+///
+/// The pixels come from capture_raw and are encoded once, so like it this refuses
+/// captures over `max_capture_pixels` rather than downscaling them. Ids are random, so a
+/// page can't guess its way to another capture's URL.
+#[tauri::command]
+pub async fn capture_screen_binary(
+    app: AppHandle,
+    state: State<'_, BinaryCaptureState>,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<BinaryCapture, String> {
+    let compression = compression.unwrap_or_default();
+    let format = format.unwrap_or_default();
+    let active = platform::active_app();
+    let raw = capture_raw(app).await?;
+    let (width, height) = (raw.width, raw.height);
+    let (bytes, mime_type) =
+        tauri::async_runtime::spawn_blocking(move || encode_raw(raw, compression, format))
+            .await
+            .map_err(|e| e.to_string())??;
+    let metadata = CaptureMetadata {
+        width,
+        height,
+        mime_type: mime_type.to_string(),
+        compression: (format == CaptureFormat::Png).then_some(compression),
+        active_app: active.name,
+        active_window_title: active.window_title,
+        downscaled: false,
+        region: None,
+        macos_backend: None,
+        rotation: 0,
+        profile: None,
+        monitor: None,
+        thumbnail_width: None,
+        thumbnail_height: None,
+        stabilized: None,
+        has_alpha: false,
+    };
+
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let mut stored = state.stored.lock().unwrap();
+    if stored.len() >= MAX_STORED {
+        stored.pop_front();
+    }
+    stored.push_back(StoredCapture {
+        id: id.clone(),
+        mime_type: metadata.mime_type.clone(),
+        bytes,
+    });
+
+    Ok(BinaryCapture {
+        url: capture_url(&id),
+        id,
+        metadata,
+    })
+}
+
+/// Encode a raw capture as an opaque image in `format`, returning the bytes and MIME type
+#[cfg_attr(not(feature = "webp"), allow(unused_variables))]
+fn encode_raw(
+    raw: RawCapture,
+    compression: PngCompression,
+    format: CaptureFormat,
+) -> Result<(Vec<u8>, &'static str), String> {
+    let rgb: Vec<u8> = if raw.channels == 4 {
+        raw.data
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect()
+    } else {
+        raw.data
+    };
+    match format {
+        CaptureFormat::Png => Ok((
+            encode_png(raw.width, raw.height, &rgb, compression)?,
+            "image/png",
+        )),
+        #[cfg(feature = "webp")]
+        CaptureFormat::Webp { quality, lossless } => Ok((
+            super::encode_webp(raw.width, raw.height, &rgb, quality, lossless),
+            "image/webp",
+        )),
+        #[cfg(not(feature = "webp"))]
+        CaptureFormat::Webp { .. } => Err("WebP output is not available in this build".to_string()),
+    }
+}
+
+/// Drop every stored binary capture
+#[tauri::command]
+pub fn clear_binary_captures(state: State<'_, BinaryCaptureState>) {
+    state.stored.lock().unwrap().clear();
+}
+
+/// Where the webview loads a stored capture from
+///
+/// Windows and Android webviews can't load custom schemes directly, so Tauri serves them
+/// from `http://<scheme>.localhost` there instead.
+fn capture_url(id: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", CAPTURE_SCHEME, id)
+    } else {
+        format!("{}://localhost/{}", CAPTURE_SCHEME, id)
+    }
+}
+
+/// Serve a stored capture for the lens-capture protocol registered in lib.rs
+pub fn handle_protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let id = request.uri().path().trim_start_matches('/');
+    let state = ctx.app_handle().state::<BinaryCaptureState>();
+    let stored = state.stored.lock().unwrap();
+
+    let response = match stored.iter().find(|capture| capture.id == id) {
+        Some(capture) => Response::builder()
+            .header(header::CONTENT_TYPE, capture.mime_type.as_str())
+            .body(capture.bytes.clone()),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new()),
+    };
+    response.unwrap_or_default()
+}
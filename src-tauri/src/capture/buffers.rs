@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+/// How many capture targets keep a buffer around
+const MAX_CACHED: usize = 4;
+
+/// What a pixel buffer was captured from: a desktop rect and its pixel size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BufferKey {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Pixel buffers from recent captures, most recently used last
+struct BufferCache(Vec<(BufferKey, Vec<u8>)>);
+
+impl BufferCache {
+    const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn take(&mut self, key: BufferKey) -> Vec<u8> {
+        self.0
+            .iter()
+            .position(|(cached, _)| *cached == key)
+            .map(|index| self.0.remove(index).1)
+            .unwrap_or_default()
+    }
+
+    fn give_back(&mut self, key: BufferKey, buffer: Vec<u8>) {
+        self.0.retain(|(cached, _)| *cached != key);
+        self.0.push((key, buffer));
+        if self.0.len() > MAX_CACHED {
+            self.0.remove(0);
+        }
+    }
+}
+
+/// A full-screen capture is tens of MB, and tight ask loops capture the same monitor over
+/// and over, so buffers are handed back after encoding and reused for the next capture of
+/// the same target instead of being reallocated.
+static CACHE: Mutex<BufferCache> = Mutex::new(BufferCache::new());
+
+/// A zeroed buffer of `len` bytes, reusing the cached allocation for `key` when there is one
+pub(crate) fn take(key: BufferKey, len: usize) -> Vec<u8> {
+    let mut buffer = CACHE.lock().unwrap().take(key);
+    buffer.clear();
+    buffer.resize(len, 0);
+    buffer
+}
+
+/// Return a buffer from `take` once its pixels have been encoded
+pub(crate) fn give_back(key: BufferKey, buffer: Vec<u8>) {
+    CACHE.lock().unwrap().give_back(key, buffer);
+}
+
+/// Drop every cached buffer; the targets they were keyed by may be gone
+pub(crate) fn clear() {
+    CACHE.lock().unwrap().0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(index: i32) -> BufferKey {
+        BufferKey {
+            x: index * 1920,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    /// Run `captures` take/give_back rounds over `keys` in turn, counting fresh allocations
+    fn allocations(cache: &mut BufferCache, keys: &[BufferKey], captures: usize) -> usize {
+        let len = 1920 * 1080 * 4;
+        let mut allocated = 0;
+        for key in keys.iter().cycle().take(captures) {
+            let mut buffer = cache.take(*key);
+            if buffer.capacity() < len {
+                allocated += 1;
+            }
+            buffer.resize(len, 0);
+            cache.give_back(*key, buffer);
+        }
+        allocated
+    }
+
+    #[test]
+    fn repeated_captures_reuse_one_buffer() {
+        let mut cache = BufferCache::new();
+        assert_eq!(allocations(&mut cache, &[monitor(0)], 50), 1);
+    }
+
+    #[test]
+    fn up_to_max_cached_targets_stay_cached() {
+        let mut cache = BufferCache::new();
+        let keys: Vec<BufferKey> = (0..MAX_CACHED as i32).map(monitor).collect();
+        assert_eq!(allocations(&mut cache, &keys, 100), MAX_CACHED);
+    }
+
+    #[test]
+    fn least_recently_used_target_is_evicted() {
+        let mut cache = BufferCache::new();
+        let keys: Vec<BufferKey> = (0..=MAX_CACHED as i32).map(monitor).collect();
+        allocations(&mut cache, &keys, keys.len());
+
+        assert!(cache.take(monitor(0)).is_empty());
+        assert!(!cache.take(monitor(MAX_CACHED as i32)).is_empty());
+    }
+}
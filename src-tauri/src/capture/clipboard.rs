@@ -0,0 +1,74 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use tauri::AppHandle;
+
+use super::{
+    convert_format, encode_png, run_capture, CaptureFormat, CaptureMetadata, CaptureResult,
+    PngCompression,
+};
+
+/// Use the image on the clipboard instead of capturing the screen
+///
+/// Goes through the same size limits and verification as a screen capture, so an image
+/// pasted from an OS screenshot tool comes back in exactly the capture_screen shape.
+#[tauri::command]
+pub fn capture_from_clipboard(
+    app: AppHandle,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<CaptureResult, String> {
+    let compression = compression.unwrap_or_default();
+    let mut result = run_capture(&app, false, None, compression, || {
+        read_clipboard_image(compression)
+    })?;
+
+    // The image wasn't taken from whatever app is in front now
+    result.metadata.active_app = None;
+    result.metadata.active_window_title = None;
+    convert_format(&mut result, format.unwrap_or_default())?;
+    Ok(result)
+}
+
+fn read_clipboard_image(compression: PngCompression) -> Result<CaptureResult, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    let image = clipboard.get_image().map_err(|e| match e {
+        arboard::Error::ContentNotAvailable => "Clipboard does not contain an image".to_string(),
+        e => e.to_string(),
+    })?;
+
+    let (width, height) = (image.width as u32, image.height as u32);
+    if width == 0 || height == 0 {
+        return Err("Clipboard image is empty".to_string());
+    }
+
+    // Captures are RGB; drop the alpha channel
+    let rgb: Vec<u8> = image
+        .bytes
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+    let png_data = encode_png(width, height, &rgb, compression)?;
+
+    Ok(CaptureResult {
+        data: BASE64_STANDARD.encode(&png_data),
+        metadata: CaptureMetadata {
+            width,
+            height,
+            mime_type: "image/png".to_string(),
+            compression: Some(compression),
+            active_app: None,
+            active_window_title: None,
+            downscaled: false,
+            region: None,
+            macos_backend: None,
+            rotation: 0,
+            profile: None,
+            monitor: None,
+            thumbnail_width: None,
+            thumbnail_height: None,
+            stabilized: None,
+            has_alpha: false,
+        },
+        thumbnail: None,
+    })
+}
@@ -0,0 +1,91 @@
+use super::{capture_screen, CaptureOptions, CaptureResult, PngCompression};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const MAX_COUNTDOWN_SECONDS: u32 = 60;
+
+/// The running capture countdown, if any
+#[derive(Default)]
+pub struct CountdownState {
+    /// Generation of the running countdown, or 0; clearing it cancels the countdown, and a
+    /// cancelled one can neither capture nor end a newer countdown
+    active: AtomicU64,
+    generation: AtomicU64,
+}
+
+/// Count down `seconds`, emitting `capture-countdown` with the remaining seconds, then capture
+///
+/// The backend owns the timing so the last number shown is exactly one second before the
+/// capture. Fails with "Countdown cancelled" after cancel_countdown.
+#[tauri::command]
+pub async fn capture_with_countdown(
+    app: AppHandle,
+    state: State<'_, CountdownState>,
+    seconds: u32,
+    compression: Option<PngCompression>,
+) -> Result<CaptureResult, String> {
+    if seconds > MAX_COUNTDOWN_SECONDS {
+        return Err(format!("seconds must be at most {}", MAX_COUNTDOWN_SECONDS));
+    }
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if state
+        .active
+        .compare_exchange(0, generation, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("A capture countdown is already running".to_string());
+    }
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let result = run_countdown(&app, seconds, generation).and_then(|_| {
+            capture_screen(
+                app.clone(),
+                Some(CaptureOptions::with_compression(compression)),
+            )
+        });
+        // Only clear our own run; a newer countdown may already have started
+        let _ = app.state::<CountdownState>().active.compare_exchange(
+            generation,
+            0,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    result
+}
+
+#[tauri::command]
+pub fn cancel_countdown(state: State<'_, CountdownState>) {
+    state.active.store(0, Ordering::SeqCst);
+}
+
+/// Count down, failing as soon as this countdown is no longer the active one
+fn run_countdown(app: &AppHandle, seconds: u32, generation: u64) -> Result<(), String> {
+    let state = app.state::<CountdownState>();
+    let running = || state.active.load(Ordering::SeqCst) == generation;
+
+    for remaining in (1..=seconds).rev() {
+        let _ = app.emit("capture-countdown", remaining);
+
+        let tick_end = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < tick_end {
+            if !running() {
+                return Err("Countdown cancelled".to_string());
+            }
+            // Sleep in short slices so a cancel request is picked up promptly
+            std::thread::sleep((tick_end - Instant::now()).min(Duration::from_millis(50)));
+        }
+    }
+
+    // Checked once more right before the capture
+    if running() {
+        Ok(())
+    } else {
+        Err("Countdown cancelled".to_string())
+    }
+}
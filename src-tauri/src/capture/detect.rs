@@ -0,0 +1,60 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::platform;
+
+/// How often the recorder check runs in the background
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Apps whose only job is recording or streaming the screen, lowercased
+///
+/// Neither macOS nor Windows says whether the screen is being captured (macOS's `isCaptured`
+/// is iOS-only), so a known recorder running is the best signal there is. General apps that
+/// can share a screen, like video call clients, are left out to avoid constant false alarms.
+const KNOWN_RECORDERS: &[&str] = &[
+    "obs",
+    "obs32",
+    "obs64",
+    "obs studio",
+    "streamlabs",
+    "streamlabs obs",
+    "xsplit.core",
+    "loom",
+    "camtasia",
+    "camtasiastudio",
+    "screenflow",
+    "kap",
+    "bandicam",
+    "bdcam",
+    "screencaptureui",
+];
+
+/// Whether a known screen recorder or streaming app is running
+///
+/// A heuristic; always false where running apps can't be listed.
+#[tauri::command]
+pub fn is_screen_being_captured() -> bool {
+    platform::running_app_names()
+        .iter()
+        .any(|name| KNOWN_RECORDERS.contains(&name.to_lowercase().as_str()))
+}
+
+/// Emit `screen-capture-detected` with the new state whenever is_screen_being_captured changes
+pub fn watch_screen_capture(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut captured = false;
+        loop {
+            if crate::background::is_paused(&app) {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            let now = is_screen_being_captured();
+            if now != captured {
+                captured = now;
+                let _ = app.emit("screen-capture-detected", captured);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,111 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use image::{imageops, DynamicImage, GrayImage, RgbaImage};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use super::{capture_region, platform, CaptureRect};
+
+/// Side of the grid captures are shrunk to before comparing
+const DIFF_GRID: u32 = 64;
+/// Brightness difference (0-255) a grid cell needs before it counts as changed
+const CELL_THRESHOLD: u8 = 16;
+/// How many past captures are kept to diff against
+const MAX_STORED: usize = 16;
+
+/// Downsampled captures from capture_diff, oldest first
+#[derive(Default)]
+pub struct CaptureDiffState {
+    stored: Mutex<VecDeque<(String, GrayImage)>>,
+    next_id: AtomicU64,
+}
+
+/// How much a region changed since an earlier capture_diff
+#[derive(Debug, Serialize, Clone)]
+pub struct DiffResult {
+    pub changed: bool,
+    /// Share of the region that changed, from 0.0 to 1.0
+    pub changed_fraction: f64,
+    /// Pass as `prev_id` next time to compare against this capture
+    pub capture_id: String,
+}
+
+/// Capture a region and report how much of it changed since the capture `prev_id`
+///
+/// Both captures are shrunk to a small grayscale grid first, so this is cheap enough to
+/// poll. Without a `prev_id` there is nothing to compare to and the whole region counts as
+/// changed. Only the last few captures are kept, so an old id may have been dropped.
+#[tauri::command]
+pub fn capture_diff(
+    app: AppHandle,
+    state: State<'_, CaptureDiffState>,
+    region: CaptureRect,
+    prev_id: Option<String>,
+) -> Result<DiffResult, String> {
+    let previous = match &prev_id {
+        Some(id) => Some(
+            state
+                .stored
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(stored_id, _)| stored_id == id)
+                .map(|(_, grid)| grid.clone())
+                .ok_or_else(|| format!("Unknown capture id {}", id))?,
+        ),
+        None => None,
+    };
+
+    let capture = capture_region(app, region, None, None)?;
+    let bytes = BASE64_STANDARD
+        .decode(&capture.data)
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    let grid = diff_grid(&image);
+
+    let changed_fraction = previous.map_or(1.0, |previous| changed_fraction(&previous, &grid));
+
+    let capture_id = format!("diff-{}", state.next_id.fetch_add(1, Ordering::Relaxed));
+    let mut stored = state.stored.lock().unwrap();
+    stored.push_back((capture_id.clone(), grid));
+    while stored.len() > MAX_STORED {
+        stored.pop_front();
+    }
+
+    Ok(DiffResult {
+        changed: changed_fraction > 0.0,
+        changed_fraction,
+        capture_id,
+    })
+}
+
+/// Shrink an image to the small grayscale grid captures are compared on
+pub(super) fn diff_grid(image: &DynamicImage) -> GrayImage {
+    imageops::grayscale(&imageops::thumbnail(image, DIFF_GRID, DIFF_GRID))
+}
+
+/// A diff grid of the screen as it is now, from a cheap low-resolution frame
+///
+/// For polling: unlike capture_raw this never goes through a file or a full-resolution
+/// copy of the screen. None when the frame couldn't be taken.
+pub(super) fn preview_grid() -> Option<GrayImage> {
+    let raw = platform::capture_preview().ok()?;
+    RgbaImage::from_raw(raw.width, raw.height, raw.data)
+        .map(|image| diff_grid(&DynamicImage::ImageRgba8(image)))
+}
+
+/// Share of grid cells whose brightness moved by more than CELL_THRESHOLD
+pub(super) fn changed_fraction(previous: &GrayImage, current: &GrayImage) -> f64 {
+    if previous.dimensions() != current.dimensions() {
+        return 1.0;
+    }
+    let changed_cells = previous
+        .pixels()
+        .zip(current.pixels())
+        .filter(|(a, b)| a.0[0].abs_diff(b.0[0]) > CELL_THRESHOLD)
+        .count();
+    changed_cells as f64 / (current.width() * current.height()) as f64
+}
@@ -4,21 +4,384 @@ use std::fs;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Capture screen on macOS using the native screencapture command
-#[tauri::command]
-pub fn capture_screen() -> Result<String, String> {
+use crate::config::MacosCaptureBackend;
+
+use super::buffers::{self, BufferKey};
+use super::{
+    encode_png, encode_rgba_png, ActiveApp, CaptureMetadata, CaptureRect, CaptureResult,
+    PngCompression, RawCapture, RgbaMode, TEMP_CAPTURE_PREFIX,
+};
+
+/// Capture the main display on macOS with the configured backend
+///
+/// With the screencapture command the PNG is encoded by the OS, so the compression level
+/// does not apply and `alpha` is whatever the OS wrote.
+pub fn capture_screen(
+    compression: PngCompression,
+    backend: MacosCaptureBackend,
+    alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    match resolve_backend(backend)? {
+        MacosCaptureBackend::CoreGraphics => {
+            use core_graphics::display::CGDisplay;
+            core_graphics_capture(CGDisplay::main().bounds(), compression, alpha)
+        }
+        _ => screencapture(&[]),
+    }
+}
+
+/// Capture the main display on macOS as RGBA
+pub fn capture_raw(backend: MacosCaptureBackend) -> Result<RawCapture, String> {
+    if resolve_backend(backend)? == MacosCaptureBackend::CoreGraphics {
+        use core_graphics::display::CGDisplay;
+        let (_, width, height, data) = core_graphics_pixels(CGDisplay::main().bounds(), 4)?;
+        return Ok(RawCapture {
+            width,
+            height,
+            channels: 4,
+            data,
+        });
+    }
+
+    let capture = screencapture(&[])?;
+    let bytes = BASE64_STANDARD
+        .decode(&capture.data)
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+    Ok(RawCapture {
+        width: image.width(),
+        height: image.height(),
+        channels: 4,
+        data: image.into_raw(),
+    })
+}
+
+/// A frame of the main display for change detection, at 1x rather than Retina resolution
+///
+/// Always CoreGraphics, whatever the capture backend: polling through the screencapture
+/// command would spawn a process and write a temp file on every check. The buffer cache
+/// is left alone, so polling doesn't evict the full-size capture buffers.
+pub fn capture_preview() -> Result<RawCapture, String> {
+    use core_graphics::display::CGDisplay;
+    use core_graphics::window::{
+        kCGNullWindowID, kCGWindowImageNominalResolution, kCGWindowListOptionOnScreenOnly,
+    };
+
+    let image = CGDisplay::screenshot(
+        CGDisplay::main().bounds(),
+        kCGWindowListOptionOnScreenOnly,
+        kCGNullWindowID,
+        kCGWindowImageNominalResolution,
+    )
+    .ok_or_else(|| {
+        "CoreGraphics capture failed; check the Screen Recording permission".to_string()
+    })?;
+    if image.bits_per_pixel() != 32 {
+        return Err(format!(
+            "Unsupported capture pixel format ({} bits per pixel)",
+            image.bits_per_pixel()
+        ));
+    }
+
+    let (width, height, stride) = (image.width(), image.height(), image.bytes_per_row());
+    let mut data = Vec::with_capacity(width * height * 4);
+    for row in image.data().bytes().chunks(stride).take(height) {
+        // Rows are BGRA and may be padded past the last pixel
+        for pixel in row[..width * 4].chunks_exact(4) {
+            data.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+        }
+    }
+    Ok(RawCapture {
+        width: width as u32,
+        height: height as u32,
+        channels: 4,
+        data,
+    })
+}
+
+/// Capture a rectangle of the desktop, given in physical pixels
+///
+/// Both backends take the rectangle in points, so it is converted with the scale factor of
+/// the monitor the rectangle is on.
+pub fn capture_region(
+    rect: CaptureRect,
+    scale_factor: f64,
+    compression: PngCompression,
+    backend: MacosCaptureBackend,
+    alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    let (x, y, width, height) = (
+        (rect.x as f64 / scale_factor).round(),
+        (rect.y as f64 / scale_factor).round(),
+        (rect.width as f64 / scale_factor).round(),
+        (rect.height as f64 / scale_factor).round(),
+    );
+
+    if resolve_backend(backend)? == MacosCaptureBackend::CoreGraphics {
+        use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+        let bounds = CGRect::new(&CGPoint::new(x, y), &CGSize::new(width, height));
+        return core_graphics_capture(bounds, compression, alpha);
+    }
+
+    let region = format!("-R{},{},{},{}", x, y, width, height);
+    screencapture(&[&region])
+}
+
+/// The backend a configured choice resolves to on this machine
+fn resolve_backend(requested: MacosCaptureBackend) -> Result<MacosCaptureBackend, String> {
+    use cocoa::base::nil;
+    use cocoa::foundation::{NSOperatingSystemVersion, NSProcessInfo};
+
+    match requested {
+        MacosCaptureBackend::Auto => {
+            // CGWindowListCreateImage is deprecated from macOS 14
+            let sonoma = unsafe {
+                NSProcessInfo::processInfo(nil)
+                    .isOperatingSystemAtLeastVersion(NSOperatingSystemVersion::new(14, 0, 0))
+            };
+            Ok(if sonoma {
+                MacosCaptureBackend::ScreencaptureCmd
+            } else {
+                MacosCaptureBackend::CoreGraphics
+            })
+        }
+        MacosCaptureBackend::ScreenCaptureKit => {
+            Err("The ScreenCaptureKit backend isn't available in this build".to_string())
+        }
+        backend => Ok(backend),
+    }
+}
+
+/// Capture a rectangle in global points with CoreGraphics, encoded as PNG
+fn core_graphics_capture(
+    bounds: core_graphics::geometry::CGRect,
+    compression: PngCompression,
+    alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    encode_cg_image(
+        &core_graphics_screenshot(bounds)?,
+        bounds,
+        compression,
+        alpha,
+    )
+}
+
+/// Capture a rectangle in global points with CoreGraphics, with every on-screen window
+fn core_graphics_screenshot(
+    bounds: core_graphics::geometry::CGRect,
+) -> Result<core_graphics::image::CGImage, String> {
+    use core_graphics::display::CGDisplay;
+    use core_graphics::window::{
+        kCGNullWindowID, kCGWindowImageBestResolution, kCGWindowListOptionOnScreenOnly,
+    };
+
+    CGDisplay::screenshot(
+        bounds,
+        kCGWindowListOptionOnScreenOnly,
+        kCGNullWindowID,
+        kCGWindowImageBestResolution,
+    )
+    .ok_or_else(|| "CoreGraphics capture failed; check the Screen Recording permission".to_string())
+}
+
+/// Encode a CoreGraphics capture of `bounds` as a PNG capture result, RGBA if `alpha` says so
+fn encode_cg_image(
+    image: &core_graphics::image::CGImage,
+    bounds: core_graphics::geometry::CGRect,
+    compression: PngCompression,
+    alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    let png_data = if alpha == RgbaMode::PreserveAlpha {
+        let (key, width, height, rgba) = cg_image_pixels(image, bounds, 4, alpha)?;
+        let png_data = encode_rgba_png(width, height, &rgba, compression);
+        buffers::give_back(key, rgba);
+        png_data.map(|png_data| (width, height, png_data))
+    } else {
+        let (key, width, height, rgb) = cg_image_pixels(image, bounds, 3, alpha)?;
+        let png_data = encode_png(width, height, &rgb, compression);
+        buffers::give_back(key, rgb);
+        png_data.map(|png_data| (width, height, png_data))
+    };
+    let (width, height, png_data) = png_data?;
+
+    Ok(CaptureResult {
+        data: BASE64_STANDARD.encode(png_data),
+        metadata: CaptureMetadata {
+            width,
+            height,
+            mime_type: "image/png".to_string(),
+            compression: Some(compression),
+            active_app: None,
+            active_window_title: None,
+            downscaled: false,
+            region: None,
+            macos_backend: Some(MacosCaptureBackend::CoreGraphics),
+            rotation: 0,
+            profile: None,
+            monitor: None,
+            thumbnail_width: None,
+            thumbnail_height: None,
+            stabilized: None,
+            has_alpha: false,
+        },
+        thumbnail: None,
+    })
+}
+
+/// Capture a rectangle in global points with CoreGraphics as tightly packed RGB or RGBA
+///
+/// `channels` is 3 for RGB or 4 for RGBA. The buffer comes from the capture buffer cache
+/// under the returned key; hand it back once it's been used, unless it's kept.
+fn core_graphics_pixels(
+    bounds: core_graphics::geometry::CGRect,
+    channels: usize,
+) -> Result<(BufferKey, u32, u32, Vec<u8>), String> {
+    cg_image_pixels(
+        &core_graphics_screenshot(bounds)?,
+        bounds,
+        channels,
+        RgbaMode::Opaque,
+    )
+}
+
+/// Unpack a CoreGraphics capture of `bounds` into a cached RGB or RGBA buffer
+///
+/// RGBA comes out opaque unless `alpha` is PreserveAlpha, in which case CoreGraphics'
+/// premultiplied alpha is converted to the straight alpha PNG expects.
+fn cg_image_pixels(
+    image: &core_graphics::image::CGImage,
+    bounds: core_graphics::geometry::CGRect,
+    channels: usize,
+    alpha: RgbaMode,
+) -> Result<(BufferKey, u32, u32, Vec<u8>), String> {
+    if image.bits_per_pixel() != 32 {
+        return Err(format!(
+            "Unsupported capture pixel format ({} bits per pixel)",
+            image.bits_per_pixel()
+        ));
+    }
+
+    let (width, height, stride) = (image.width(), image.height(), image.bytes_per_row());
+    let key = BufferKey {
+        x: bounds.origin.x as i32,
+        y: bounds.origin.y as i32,
+        width: width as u32,
+        height: height as u32,
+    };
+    let mut pixels = buffers::take(key, width * height * channels);
+    let data = image.data();
+    let rows = data.bytes().chunks(stride).take(height);
+    for (row, out) in rows.zip(pixels.chunks_exact_mut(width * channels)) {
+        // Rows are BGRA and may be padded past the last pixel
+        for (pixel, out) in row[..width * 4]
+            .chunks_exact(4)
+            .zip(out.chunks_exact_mut(channels))
+        {
+            out[..3].copy_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+            if channels == 4 {
+                out[3] = 255;
+                if alpha == RgbaMode::PreserveAlpha && pixel[3] < 255 {
+                    let a = pixel[3] as u16;
+                    for channel in &mut out[..3] {
+                        *channel = (*channel as u16 * 255 + a / 2)
+                            .checked_div(a)
+                            .map_or(0, |c| c.min(255) as u8);
+                    }
+                    out[3] = pixel[3];
+                }
+            }
+        }
+    }
+    Ok((key, width as u32, height as u32, pixels))
+}
+
+/// Capture the main display with every on-screen window of `pids` left out
+///
+/// Always uses CoreGraphics: the image is composited from an explicit list of the other
+/// on-screen windows, which the screencapture command can't do.
+pub fn capture_screen_excluding(
+    pids: &[u32],
+    compression: PngCompression,
+) -> Result<CaptureResult, String> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use core_foundation::array::CFArray;
+    use core_graphics::display::CGDisplay;
+    use core_graphics::window::kCGWindowImageBestResolution;
+    use objc::{msg_send, sel, sel_impl};
+    use std::ffi::c_void;
+
+    let window_ids: Vec<usize> = unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let mut ids = Vec::new();
+        let windows = CGWindowListCopyWindowInfo(
+            CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY | CG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS,
+            0,
+        );
+        if windows != nil {
+            let key = |name: &str| NSString::alloc(nil).init_str(name).autorelease();
+            let (pid_key, number_key) = (key("kCGWindowOwnerPID"), key("kCGWindowNumber"));
+            let count: usize = msg_send![windows, count];
+            // Kept front to back, the order the window list composites them in
+            for i in 0..count {
+                let info: id = msg_send![windows, objectAtIndex: i];
+                let pid: id = msg_send![info, objectForKey: pid_key];
+                let number: id = msg_send![info, objectForKey: number_key];
+                if pid == nil || number == nil {
+                    continue;
+                }
+                let pid: i32 = msg_send![pid, intValue];
+                if pids.contains(&(pid as u32)) {
+                    continue;
+                }
+                let number: u32 = msg_send![number, unsignedIntValue];
+                ids.push(number as usize);
+            }
+            let _: () = msg_send![windows, release];
+        }
+        pool.drain();
+        ids
+    };
+
+    // CGWindowListCreateImageFromArray wants the raw window ids as the array's values
+    let ids: Vec<*const c_void> = window_ids.iter().map(|&id| id as *const c_void).collect();
+    let array = CFArray::from_copyable(&ids);
+    let bounds = CGDisplay::main().bounds();
+    let image = CGDisplay::screenshot_from_windows(bounds, array, kCGWindowImageBestResolution)
+        .ok_or("CoreGraphics capture failed; check the Screen Recording permission")?;
+    encode_cg_image(&image, bounds, compression, RgbaMode::Opaque)
+}
+
+/// Whether a process with this pid is running an app
+pub fn process_exists(pid: u32) -> bool {
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let running: id = msg_send![class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: pid as i32];
+        running != nil
+    }
+}
+
+/// Run screencapture into a temp file with extra arguments and read the result back
+fn screencapture(args: &[&str]) -> Result<CaptureResult, String> {
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| e.to_string())?
         .as_millis();
-    let filename = format!("lens_app_capture_{}.png", timestamp);
+    let filename = format!("{}{}.png", TEMP_CAPTURE_PREFIX, timestamp);
     let mut path = std::env::temp_dir();
     path.push(filename);
 
     let output_path = path.to_str().ok_or("Invalid temp path")?;
 
     let status = Command::new("screencapture")
-        .args(["-x", output_path])
+        .arg("-x")
+        .args(args)
+        .arg(output_path)
         .status()
         .map_err(|e| e.to_string())?;
 
@@ -29,5 +392,196 @@ pub fn capture_screen() -> Result<String, String> {
     let bytes = fs::read(&path).map_err(|e| e.to_string())?;
     fs::remove_file(&path).map_err(|e| e.to_string())?;
 
-    Ok(BASE64_STANDARD.encode(bytes))
+    let (width, height) = png_dimensions(&bytes).unwrap_or((0, 0));
+
+    Ok(CaptureResult {
+        data: BASE64_STANDARD.encode(bytes),
+        metadata: CaptureMetadata {
+            width,
+            height,
+            mime_type: "image/png".to_string(),
+            compression: None,
+            active_app: None,
+            active_window_title: None,
+            downscaled: false,
+            region: None,
+            macos_backend: Some(MacosCaptureBackend::ScreencaptureCmd),
+            rotation: 0,
+            profile: None,
+            monitor: None,
+            thumbnail_width: None,
+            thumbnail_height: None,
+            stabilized: None,
+            has_alpha: false,
+        },
+        thumbnail: None,
+    })
+}
+
+/// Read the width and height from a PNG's IHDR chunk
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.len() < 24 || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> cocoa::base::id;
+}
+
+const CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY: u32 = 1 << 0;
+const CG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+
+/// Find the frontmost app and its top window title
+///
+/// When our own overlay is frontmost, the app whose window sits directly behind it is
+/// reported instead. Window titles need the Screen Recording permission, which capture
+/// already requires.
+pub fn active_app() -> ActiveApp {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe fn to_string(ns_string: id) -> Option<String> {
+        if ns_string == nil {
+            return None;
+        }
+        let utf8 = ns_string.UTF8String();
+        if utf8.is_null() {
+            return None;
+        }
+        let s = std::ffi::CStr::from_ptr(utf8)
+            .to_string_lossy()
+            .into_owned();
+        (!s.is_empty()).then_some(s)
+    }
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let own_pid = std::process::id() as i32;
+
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let frontmost: id = msg_send![workspace, frontmostApplication];
+        let frontmost_pid: i32 = if frontmost == nil {
+            -1
+        } else {
+            msg_send![frontmost, processIdentifier]
+        };
+        let frontmost_is_us = frontmost_pid == own_pid;
+
+        let mut active = ActiveApp::default();
+        if !frontmost_is_us && frontmost != nil {
+            active.name = to_string(msg_send![frontmost, localizedName]);
+        }
+
+        // Windows come back front to back, so the first normal-layer window that isn't
+        // ours belongs to the app the user is looking at
+        let windows = CGWindowListCopyWindowInfo(
+            CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY | CG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS,
+            0,
+        );
+        if windows != nil {
+            let key = |name: &str| NSString::alloc(nil).init_str(name).autorelease();
+            let (layer_key, pid_key, owner_key, name_key) = (
+                key("kCGWindowLayer"),
+                key("kCGWindowOwnerPID"),
+                key("kCGWindowOwnerName"),
+                key("kCGWindowName"),
+            );
+
+            let count: usize = msg_send![windows, count];
+            for i in 0..count {
+                let info: id = msg_send![windows, objectAtIndex: i];
+                let layer: id = msg_send![info, objectForKey: layer_key];
+                let pid: id = msg_send![info, objectForKey: pid_key];
+                if layer == nil || pid == nil {
+                    continue;
+                }
+                let layer: i32 = msg_send![layer, intValue];
+                let pid: i32 = msg_send![pid, intValue];
+                if layer != 0 || pid == own_pid || (!frontmost_is_us && pid != frontmost_pid) {
+                    continue;
+                }
+
+                if active.name.is_none() {
+                    active.name = to_string(msg_send![info, objectForKey: owner_key]);
+                }
+                active.window_title = to_string(msg_send![info, objectForKey: name_key]);
+                break;
+            }
+            let _: () = msg_send![windows, release];
+        }
+
+        pool.drain();
+        active
+    }
+}
+
+/// Localized names of the running apps
+pub fn running_app_names() -> Vec<String> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let pool = NSAutoreleasePool::new(nil);
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let apps: id = msg_send![workspace, runningApplications];
+        let mut names = Vec::new();
+        if apps != nil {
+            let count: usize = msg_send![apps, count];
+            for i in 0..count {
+                let app: id = msg_send![apps, objectAtIndex: i];
+                let name: id = msg_send![app, localizedName];
+                if name == nil {
+                    continue;
+                }
+                let utf8 = name.UTF8String();
+                if !utf8.is_null() {
+                    names.push(
+                        std::ffi::CStr::from_ptr(utf8)
+                            .to_string_lossy()
+                            .into_owned(),
+                    );
+                }
+            }
+        }
+        pool.drain();
+        names
+    }
+}
+
+/// A point on the screen holding the key window, in physical desktop pixels
+///
+/// NSScreen.mainScreen is the screen with keyboard focus. Its center is flipped to the
+/// top-left origin monitors use and scaled by that screen's own backing factor, which is how
+/// monitor positions are reported on macOS.
+pub fn active_monitor_point() -> Option<(f64, f64)> {
+    use cocoa::appkit::NSScreen;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSArray;
+
+    unsafe {
+        let screen: id = NSScreen::mainScreen(nil);
+        let screens: id = NSScreen::screens(nil);
+        if screen == nil || screens == nil || screens.count() == 0 {
+            return None;
+        }
+        let primary_height = NSScreen::frame(screens.objectAtIndex(0)).size.height;
+        let frame = NSScreen::frame(screen);
+        let scale = NSScreen::backingScaleFactor(screen);
+
+        let x = frame.origin.x + frame.size.width / 2.0;
+        let y = primary_height - (frame.origin.y + frame.size.height / 2.0);
+        Some((x * scale, y * scale))
+    }
+}
+
+/// Whether Screen Recording permission has been granted, checked without prompting
+pub fn can_capture() -> bool {
+    core_graphics::access::ScreenCaptureAccess.preflight()
 }
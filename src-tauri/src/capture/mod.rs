@@ -1,14 +1,1077 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{self, CaptureRotation, MacosCaptureBackend, OversizeCapture};
+use crate::last_errors::{self, Subsystem};
+use redact::Redaction;
+use watermark::WatermarkOptions;
+
+// Only the native capture backends have pixel buffers to reuse
+pub mod binary;
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+pub(crate) mod buffers;
+pub mod clipboard;
+pub mod countdown;
+pub mod detect;
+pub mod diff;
+pub mod profiles;
+pub mod redact;
+pub mod stable;
+pub mod stream;
+pub mod upload;
+pub mod watch;
+pub mod watermark;
+
 #[cfg(target_os = "macos")]
 mod macos;
 #[cfg(target_os = "macos")]
-pub use macos::capture_screen;
+use macos as platform;
 
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "windows")]
-pub use windows::capture_screen;
+use windows as platform;
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 mod unsupported;
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub use unsupported::capture_screen;
+use unsupported as platform;
+
+/// Filename prefix for captures written to the temp dir by the file-based backend
+pub(crate) const TEMP_CAPTURE_PREFIX: &str = "lens_app_capture_";
+
+/// Temp captures older than this can't belong to an in-flight capture
+const STALE_CAPTURE_AGE: Duration = Duration::from_secs(60);
+
+/// PNG compression level for captures encoded in-process
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum PngCompression {
+    /// Fastest encoding, larger output. Default since interactive captures are latency-bound
+    #[default]
+    Fast,
+    Default,
+    Best,
+}
+
+impl From<PngCompression> for png::Compression {
+    fn from(level: PngCompression) -> Self {
+        match level {
+            PngCompression::Fast => png::Compression::Fast,
+            PngCompression::Default => png::Compression::Default,
+            PngCompression::Best => png::Compression::Best,
+        }
+    }
+}
+
+/// Encoding of the returned image
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum CaptureFormat {
+    #[default]
+    Png,
+    /// Needs the `webp` cargo feature. Lossless is usually smaller than PNG for screenshots;
+    /// lossy can come out larger on text-heavy screens. `quality` (0-100) only applies to
+    /// lossy encoding.
+    Webp {
+        #[serde(default = "default_webp_quality")]
+        quality: u8,
+        #[serde(default)]
+        lossless: bool,
+    },
+}
+
+/// Whether a capture keeps the alpha channel the screen content came with
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum RgbaMode {
+    /// Flatten to RGB, as every capture did before
+    #[default]
+    Opaque,
+    /// Encode an RGBA PNG where the backend reports alpha, e.g. macOS overlays and popovers.
+    /// Windows captures through GDI, which has no alpha, so they stay opaque there.
+    PreserveAlpha,
+}
+
+fn default_webp_quality() -> u8 {
+    80
+}
+
+/// Details about how a capture was produced
+#[derive(Debug, Serialize, Clone)]
+pub struct CaptureMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+    /// Compression level used, or None when the image was encoded by the OS
+    pub compression: Option<PngCompression>,
+    /// Frontmost app at capture time, looking past our own overlay
+    pub active_app: Option<String>,
+    pub active_window_title: Option<String>,
+    /// Set when the capture was scaled down to fit `max_capture_pixels`
+    pub downscaled: bool,
+    /// Desktop rectangle that was captured, for region captures
+    pub region: Option<CaptureRect>,
+    /// Backend that took the capture on macOS, after resolving Auto
+    pub macos_backend: Option<MacosCaptureBackend>,
+    /// Clockwise rotation in degrees applied to correct for a rotated display
+    pub rotation: u16,
+    /// Capture profile whose settings were applied, for capture_with_profile
+    pub profile: Option<String>,
+    /// Bounds of the monitor a full-screen capture came from; for the primary monitor this
+    /// is only filled in on Windows
+    pub monitor: Option<CaptureRect>,
+    /// Size of the thumbnail, when one was asked for
+    pub thumbnail_width: Option<u32>,
+    pub thumbnail_height: Option<u32>,
+    /// For capture_when_stable: whether the screen settled before the timeout
+    pub stabilized: Option<bool>,
+    /// Whether the image kept an alpha channel, only ever set for RgbaMode::PreserveAlpha
+    pub has_alpha: bool,
+}
+
+/// The app and window the user was looking at, as far as the OS will tell us
+#[derive(Debug, Default)]
+pub(crate) struct ActiveApp {
+    pub name: Option<String>,
+    pub window_title: Option<String>,
+}
+
+/// A rectangle of the desktop in physical pixels, in global (virtual desktop) coordinates
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct CaptureRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A base64-encoded capture plus its metadata
+#[derive(Debug, Serialize, Clone)]
+pub struct CaptureResult {
+    pub data: String,
+    pub metadata: CaptureMetadata,
+    /// Base64 PNG preview at most THUMBNAIL_MAX_SIDE pixels across, when one was asked for
+    pub thumbnail: Option<String>,
+}
+
+/// An unencoded capture: `channels` bytes per pixel, rows top to bottom, no padding
+#[derive(Debug, Serialize, Clone)]
+pub struct RawCapture {
+    pub width: u32,
+    pub height: u32,
+    pub channels: u8,
+    pub data: Vec<u8>,
+}
+
+/// Encode tightly packed 8-bit RGB pixels as a PNG
+pub(crate) fn encode_png(
+    width: u32,
+    height: u32,
+    rgb: &[u8],
+    compression: PngCompression,
+) -> Result<Vec<u8>, String> {
+    encode_png_pixels(width, height, rgb, png::ColorType::Rgb, compression)
+}
+
+/// Encode tightly packed 8-bit RGBA pixels, with straight alpha, as a PNG
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn encode_rgba_png(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    compression: PngCompression,
+) -> Result<Vec<u8>, String> {
+    encode_png_pixels(width, height, rgba, png::ColorType::Rgba, compression)
+}
+
+/// Encode tightly packed 8-bit RGB pixels as WebP; `quality` only applies when lossy
+#[cfg(feature = "webp")]
+pub(crate) fn encode_webp(
+    width: u32,
+    height: u32,
+    rgb: &[u8],
+    quality: u8,
+    lossless: bool,
+) -> Vec<u8> {
+    let encoder = webp::Encoder::from_rgb(rgb, width, height);
+    let data = if lossless {
+        encoder.encode_lossless()
+    } else {
+        encoder.encode(quality.min(100) as f32)
+    };
+    data.to_vec()
+}
+
+fn encode_png_pixels(
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    color: png::ColorType,
+    compression: PngCompression,
+) -> Result<Vec<u8>, String> {
+    let mut png_data: Vec<u8> = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png_data, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(compression.into());
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(pixels).map_err(|e| e.to_string())?;
+    }
+    Ok(png_data)
+}
+
+/// Whether a base64 capture is a PNG with an alpha channel, read from its IHDR color type
+fn png_has_alpha(data: &str) -> bool {
+    // 36 base64 characters cover the signature and the IHDR chunk up to its color type
+    let Some(header) = data.get(..36) else {
+        return false;
+    };
+    BASE64_STANDARD.decode(header).is_ok_and(|bytes| {
+        &bytes[1..4] == b"PNG" && &bytes[12..16] == b"IHDR" && matches!(bytes[25], 4 | 6)
+    })
+}
+
+/// Decode a capture's image data into opaque RGB pixels
+fn decode_capture(result: &CaptureResult) -> Result<image::RgbImage, String> {
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    Ok(image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgb8())
+}
+
+/// Re-encode a PNG capture in the requested format
+fn convert_format(result: &mut CaptureResult, format: CaptureFormat) -> Result<(), String> {
+    match format {
+        CaptureFormat::Png => Ok(()),
+        CaptureFormat::Webp { .. } => encode_format(result, format, &decode_capture(result)?),
+    }
+}
+
+/// Replace a PNG capture's data with `image`, its decoded pixels, in the requested format
+#[cfg_attr(not(feature = "webp"), allow(unused_variables))]
+fn encode_format(
+    result: &mut CaptureResult,
+    format: CaptureFormat,
+    image: &image::RgbImage,
+) -> Result<(), String> {
+    match format {
+        CaptureFormat::Png => Ok(()),
+        #[cfg(feature = "webp")]
+        CaptureFormat::Webp { quality, lossless } => {
+            let webp_data = encode_webp(
+                image.width(),
+                image.height(),
+                image.as_raw(),
+                quality,
+                lossless,
+            );
+            result.data = BASE64_STANDARD.encode(webp_data);
+            result.metadata.mime_type = "image/webp".to_string();
+            result.metadata.compression = None;
+            Ok(())
+        }
+        #[cfg(not(feature = "webp"))]
+        CaptureFormat::Webp { .. } => Err("WebP output is not available in this build".to_string()),
+    }
+}
+
+/// Re-decode an encoded capture and reject degenerate output
+///
+/// A failed screen read (e.g. an unchecked GetDIBits) typically still encodes fine but
+/// comes out as a single flat color, so for `full_screen` captures that is treated as an
+/// error. A region can legitimately be one color, so only its dimensions are checked.
+fn verify_capture(result: &CaptureResult, full_screen: bool) -> Result<(), String> {
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| format!("Capture verification failed: {}", e))?;
+    // Not png::Decoder directly: the Windows backend may fall back to BMP
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Capture verification failed: {}", e))?;
+
+    if image.width() != result.metadata.width || image.height() != result.metadata.height {
+        return Err(format!(
+            "Capture verification failed: decoded {}x{}, expected {}x{}",
+            image.width(),
+            image.height(),
+            result.metadata.width,
+            result.metadata.height
+        ));
+    }
+
+    if !full_screen {
+        return Ok(());
+    }
+
+    let bytes_per_pixel = image.color().bytes_per_pixel() as usize;
+    let pixels = image.as_bytes();
+    let mut chunks = pixels.chunks_exact(bytes_per_pixel.max(1));
+    if let Some(first) = chunks.next() {
+        if chunks.all(|pixel| pixel == first) {
+            return Err("Capture verification failed: image is a single flat color".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Scale a capture down so it has at most `max_pixels` pixels, re-encoding it as PNG
+fn downscale_capture(
+    result: &mut CaptureResult,
+    max_pixels: u64,
+    compression: PngCompression,
+) -> Result<(), String> {
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgb8();
+
+    let pixels = image.width() as f64 * image.height() as f64;
+    let factor = (max_pixels as f64 / pixels).sqrt();
+    let width = ((image.width() as f64 * factor).floor() as u32).max(1);
+    let height = ((image.height() as f64 * factor).floor() as u32).max(1);
+    let resized =
+        image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle);
+
+    let png_data = encode_png(width, height, resized.as_raw(), compression)?;
+    result.data = BASE64_STANDARD.encode(png_data);
+    result.metadata.width = width;
+    result.metadata.height = height;
+    result.metadata.compression = Some(compression);
+    result.metadata.downscaled = true;
+    Ok(())
+}
+
+/// Longest side of a capture thumbnail, in pixels
+const THUMBNAIL_MAX_SIDE: u32 = 256;
+
+/// Attach a PNG thumbnail of `image`, the capture's decoded pixels, no larger than
+/// THUMBNAIL_MAX_SIDE on either side
+fn add_thumbnail(result: &mut CaptureResult, image: &image::RgbImage) -> Result<(), String> {
+    let scale = (THUMBNAIL_MAX_SIDE as f64 / image.width().max(image.height()) as f64).min(1.0);
+    let thumbnail = image::imageops::thumbnail(
+        image,
+        ((image.width() as f64 * scale).round() as u32).max(1),
+        ((image.height() as f64 * scale).round() as u32).max(1),
+    );
+
+    let png_data = encode_png(
+        thumbnail.width(),
+        thumbnail.height(),
+        thumbnail.as_raw(),
+        PngCompression::Fast,
+    )?;
+    result.thumbnail = Some(BASE64_STANDARD.encode(png_data));
+    result.metadata.thumbnail_width = Some(thumbnail.width());
+    result.metadata.thumbnail_height = Some(thumbnail.height());
+    Ok(())
+}
+
+/// Rotate a capture clockwise by `degrees` (90, 180 or 270), re-encoding it as PNG
+fn rotate_capture(
+    result: &mut CaptureResult,
+    degrees: u16,
+    compression: PngCompression,
+) -> Result<(), String> {
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgb8();
+    let rotated = match degrees {
+        90 => image::imageops::rotate90(&image),
+        180 => image::imageops::rotate180(&image),
+        270 => image::imageops::rotate270(&image),
+        _ => return Ok(()),
+    };
+
+    let png_data = encode_png(
+        rotated.width(),
+        rotated.height(),
+        rotated.as_raw(),
+        compression,
+    )?;
+    result.data = BASE64_STANDARD.encode(png_data);
+    result.metadata.width = rotated.width();
+    result.metadata.height = rotated.height();
+    result.metadata.mime_type = "image/png".to_string();
+    result.metadata.compression = Some(compression);
+    result.metadata.rotation = degrees;
+    Ok(())
+}
+
+/// Clockwise rotation to apply to a capture_screen capture
+///
+/// GDI and CoreGraphics already return captures the way a rotated display shows them, so
+/// only a manual override rotates anything.
+fn capture_rotation(setting: CaptureRotation) -> u16 {
+    match setting {
+        CaptureRotation::None => 0,
+        CaptureRotation::Rotate90 => 90,
+        CaptureRotation::Rotate180 => 180,
+        CaptureRotation::Rotate270 => 270,
+    }
+}
+
+/// Run a platform capture, then enforce the size limit, attach the frontmost app and verify
+///
+/// `expected_size` lets a capture that would be refused fail before any pixels are read.
+/// `full_screen` says whether a whole display was captured, for verify_capture.
+fn run_capture(
+    app: &AppHandle,
+    full_screen: bool,
+    expected_size: Option<(u32, u32)>,
+    compression: PngCompression,
+    capture: impl FnOnce() -> Result<CaptureResult, String>,
+) -> Result<CaptureResult, String> {
+    crate::commands::idle::record_activity(app);
+
+    let config = config::load_config(app);
+    let limits = &config.capture;
+    let too_large =
+        |width: u32, height: u32| width as u64 * height as u64 > limits.max_capture_pixels;
+    let refuse = |width: u32, height: u32| {
+        Err(format!(
+            "Capture of {}x{} exceeds the {} pixel limit",
+            width, height, limits.max_capture_pixels
+        ))
+    };
+
+    if let Some((width, height)) = expected_size {
+        if limits.oversize_capture == OversizeCapture::Refuse && too_large(width, height) {
+            return refuse(width, height);
+        }
+    }
+
+    let active = platform::active_app();
+    let mut result = last_errors::track(app, Subsystem::Capture, capture())?;
+    result.metadata.active_app = active.name;
+    result.metadata.active_window_title = active.window_title;
+
+    let (width, height) = (result.metadata.width, result.metadata.height);
+    if too_large(width, height) {
+        match limits.oversize_capture {
+            OversizeCapture::Refuse => return refuse(width, height),
+            OversizeCapture::Downscale => {
+                downscale_capture(&mut result, limits.max_capture_pixels, compression)?
+            }
+        }
+    }
+
+    if config.verify_captures {
+        verify_capture(&result, full_screen)?;
+    }
+
+    Ok(result)
+}
+
+/// How capture_screen should take and post-process a capture; every field is optional
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CaptureOptions {
+    pub compression: PngCompression,
+    /// Text burned in after any redactions
+    pub watermark: Option<WatermarkOptions>,
+    pub format: CaptureFormat,
+    /// Areas blacked out or blurred before the watermark, in image pixels
+    pub redactions: Vec<Redaction>,
+    /// Return a small preview of the finished image alongside it
+    pub thumbnail: bool,
+    /// Capture only the monitor's work area, leaving out the menu bar, Dock or taskbar; the
+    /// captured rectangle is returned in the metadata region
+    pub exclude_system_chrome: bool,
+    /// Lift content protection from our windows for the capture so they show up in it
+    pub ignore_protection: bool,
+    /// PreserveAlpha keeps transparency where the platform has it; redactions, a watermark,
+    /// rotation, downscaling or WebP flatten it again, and metadata.has_alpha reports what
+    /// survived
+    pub rgba_mode: RgbaMode,
+}
+
+impl CaptureOptions {
+    /// Defaults apart from the PNG compression level
+    pub fn with_compression(compression: Option<PngCompression>) -> Self {
+        Self {
+            compression: compression.unwrap_or_default(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Capture the primary screen as a base64 PNG, post-processed as `options` asks
+#[tauri::command]
+pub fn capture_screen(
+    app: AppHandle,
+    options: Option<CaptureOptions>,
+) -> Result<CaptureResult, String> {
+    let CaptureOptions {
+        compression,
+        watermark,
+        format,
+        redactions,
+        thumbnail,
+        exclude_system_chrome,
+        ignore_protection,
+        rgba_mode,
+    } = options.unwrap_or_default();
+    let expected_size = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| (m.size().width, m.size().height));
+
+    let config = config::load_config(&app);
+    let backend = config.capture_backend();
+    let pinned = config.default_capture_monitor.as_deref().and_then(|name| {
+        crate::monitors::list_monitors(&app)
+            .into_iter()
+            .find(|m| m.name.as_deref() == Some(name))
+    });
+    let work_area = if exclude_system_chrome {
+        work_area(&app, pinned.as_ref().and_then(|m| m.name.as_deref()))
+    } else {
+        None
+    };
+    // (rect to capture, bounds of its monitor, scale factor) when not the whole primary
+    let target = work_area.or_else(|| {
+        pinned.filter(|m| !m.is_primary).map(|monitor| {
+            let bounds = CaptureRect {
+                x: monitor.x,
+                y: monitor.y,
+                width: monitor.width,
+                height: monitor.height,
+            };
+            (bounds, bounds, monitor.scale_factor)
+        })
+    });
+
+    // Restores protection when the capture returns, including on failure
+    let _lifted = ignore_protection.then(|| crate::commands::window::lift_content_protection(&app));
+
+    let mut result = match target {
+        Some((rect, bounds, scale_factor)) => {
+            let mut result = run_capture(
+                &app,
+                true,
+                Some((rect.width, rect.height)),
+                compression,
+                || platform::capture_region(rect, scale_factor, compression, backend, rgba_mode),
+            )?;
+            result.metadata.monitor = Some(bounds);
+            if rect != bounds {
+                result.metadata.region = Some(rect);
+            }
+            result
+        }
+        None => run_capture(&app, true, expected_size, compression, || {
+            platform::capture_screen(compression, backend, rgba_mode)
+        })?,
+    };
+    let rotation = capture_rotation(config.capture_rotation);
+    if rotation != 0 {
+        rotate_capture(&mut result, rotation, compression)?;
+    }
+    if !redactions.is_empty() {
+        redact::apply_redactions(&mut result, &redactions, compression)?;
+    }
+    if let Some(watermark) = &watermark {
+        watermark::apply_watermark(&app, &mut result, watermark, compression)?;
+    }
+    if thumbnail {
+        // One decode serves both the thumbnail and the final encode
+        let image = decode_capture(&result)?;
+        add_thumbnail(&mut result, &image)?;
+        encode_format(&mut result, format, &image)?;
+    } else {
+        convert_format(&mut result, format)?;
+    }
+    result.metadata.has_alpha = rgba_mode == RgbaMode::PreserveAlpha && png_has_alpha(&result.data);
+    Ok(result)
+}
+
+/// The work area of the named monitor (or the primary one) with the monitor's bounds and
+/// scale factor
+///
+/// This is the display minus the menu bar and Dock on macOS (visibleFrame), or minus the
+/// taskbar on Windows. None when no space is reserved, e.g. when those bars auto-hide,
+/// since the whole display is the work area then.
+fn work_area(app: &AppHandle, name: Option<&str>) -> Option<(CaptureRect, CaptureRect, f64)> {
+    let monitor = match name {
+        Some(name) => app
+            .available_monitors()
+            .ok()?
+            .into_iter()
+            .find(|m| m.name().map(String::as_str) == Some(name)),
+        None => None,
+    }
+    .or_else(|| app.primary_monitor().ok().flatten())?;
+
+    let bounds = CaptureRect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    };
+    let area = monitor.work_area();
+    let work = CaptureRect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    };
+    (work != bounds && work.width > 0 && work.height > 0).then_some((
+        work,
+        bounds,
+        monitor.scale_factor(),
+    ))
+}
+
+/// Capture the primary screen with the windows of other apps left out, e.g. a password manager
+///
+/// Every pid must belong to a running process. On macOS this always uses CoreGraphics,
+/// whatever backend is configured.
+#[tauri::command]
+pub fn capture_screen_excluding(
+    app: AppHandle,
+    pids: Vec<u32>,
+    compression: Option<PngCompression>,
+) -> Result<CaptureResult, String> {
+    if pids.is_empty() {
+        return Err("No pids to exclude".to_string());
+    }
+    if let Some(pid) = pids.iter().find(|&&pid| !platform::process_exists(pid)) {
+        return Err(format!("No running process with pid {}", pid));
+    }
+
+    let compression = compression.unwrap_or_default();
+    let expected_size = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| (m.size().width, m.size().height));
+    run_capture(&app, true, expected_size, compression, || {
+        platform::capture_screen_excluding(&pids, compression)
+    })
+}
+
+/// Capture the primary screen as raw RGBA pixels, skipping PNG and base64 entirely
+///
+/// The payload is big: a 4K screen is about 33 MB of pixels, and more again once sent over
+/// IPC as a JSON array. Raw captures are never downscaled, so anything over
+/// `max_capture_pixels` is refused.
+#[tauri::command]
+pub async fn capture_raw(app: AppHandle) -> Result<RawCapture, String> {
+    crate::commands::idle::record_activity(&app);
+    let config = config::load_config(&app);
+    let max_pixels = config.capture.max_capture_pixels;
+    let backend = config.capture_backend();
+
+    let raw = tauri::async_runtime::spawn_blocking(move || platform::capture_raw(backend))
+        .await
+        .map_err(|e| e.to_string())??;
+    if raw.width as u64 * raw.height as u64 > max_pixels {
+        return Err(format!(
+            "Capture of {}x{} exceeds the {} pixel limit",
+            raw.width, raw.height, max_pixels
+        ));
+    }
+    Ok(raw)
+}
+
+/// Pin capture_screen to the monitor with this name, or None to capture the primary monitor
+///
+/// Names come from get_monitors and stay stable when monitor order shuffles. A monitor
+/// that isn't connected is still saved, since it may be plugged back in; captures use
+/// the primary monitor until then. Returns whether the monitor is currently connected.
+#[tauri::command]
+pub fn set_default_capture_monitor(app: AppHandle, name: Option<String>) -> Result<bool, String> {
+    let connected = match &name {
+        Some(name) => crate::monitors::list_monitors(&app)
+            .iter()
+            .any(|m| m.name.as_ref() == Some(name)),
+        None => true,
+    };
+    if !connected {
+        eprintln!(
+            "Default capture monitor {:?} isn't connected; using the primary monitor until it is",
+            name
+        );
+    }
+
+    let mut config = config::load_config(&app);
+    config.default_capture_monitor = name;
+    config::save_config(&app, &config, "set_default_capture_monitor")?;
+    Ok(connected)
+}
+
+/// Choose how screens are captured on macOS; ignored elsewhere
+///
+/// An escape hatch for when one backend misbehaves on a particular machine.
+#[tauri::command]
+pub fn set_macos_capture_backend(
+    app: AppHandle,
+    backend: MacosCaptureBackend,
+) -> Result<(), String> {
+    if backend == MacosCaptureBackend::ScreenCaptureKit {
+        return Err("The ScreenCaptureKit backend isn't available in this build".to_string());
+    }
+    let mut config = config::load_config(&app);
+    if config.capture_memory_only && backend == MacosCaptureBackend::ScreencaptureCmd {
+        return Err(
+            "The screencapture command writes a temp file, which capture_memory_only rules out"
+                .to_string(),
+        );
+    }
+    config.macos_capture_backend = backend;
+    config::save_config(&app, &config, "set_macos_capture_backend")
+}
+
+/// Guarantee that no capture is ever written to disk, even as a temp file
+///
+/// On macOS this pins the in-memory CoreGraphics backend in place of the screencapture
+/// command, whatever macos_capture_backend says, and set_macos_capture_backend refuses the
+/// command while it's on. Windows and Linux captures never use a file.
+#[tauri::command]
+pub fn set_capture_memory_only(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.capture_memory_only = enabled;
+    config::save_config(&app, &config, "set_capture_memory_only")
+}
+
+/// Capture a rectangle of the desktop as a base64 PNG
+#[tauri::command]
+pub fn capture_region(
+    app: AppHandle,
+    rect: CaptureRect,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<CaptureResult, String> {
+    if rect.width == 0 || rect.height == 0 {
+        return Err("Capture region is empty".to_string());
+    }
+
+    let compression = compression.unwrap_or_default();
+    let scale_factor = app
+        .monitor_from_point(rect.x as f64, rect.y as f64)
+        .ok()
+        .flatten()
+        .map(|m| m.scale_factor())
+        .unwrap_or(1.0);
+    let backend = config::load_config(&app).capture_backend();
+    let mut result = run_capture(
+        &app,
+        false,
+        Some((rect.width, rect.height)),
+        compression,
+        || platform::capture_region(rect, scale_factor, compression, backend, RgbaMode::Opaque),
+    )?;
+    result.metadata.region = Some(rect);
+    convert_format(&mut result, format.unwrap_or_default())?;
+    Ok(result)
+}
+
+/// Capture the whole monitor the focused window is on, falling back to the primary monitor
+///
+/// The captured monitor's bounds are returned in the metadata region.
+#[tauri::command]
+pub fn capture_active_monitor(
+    app: AppHandle,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<CaptureResult, String> {
+    let monitor = platform::active_monitor_point()
+        .and_then(|(x, y)| app.monitor_from_point(x, y).ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten())
+        .ok_or("No monitor found")?;
+    let rect = CaptureRect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    };
+    capture_region(app, rect, compression, format)
+}
+
+/// Capture a box of `padding` pixels around a point, shifted or shrunk to fit its monitor
+///
+/// Coordinates are physical desktop pixels. The rectangle actually captured is returned in
+/// the metadata, since clamping can move it off-center.
+#[tauri::command]
+pub fn capture_region_around(
+    app: AppHandle,
+    x: i32,
+    y: i32,
+    padding: u32,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<CaptureResult, String> {
+    if padding == 0 {
+        return Err("padding must be greater than 0".to_string());
+    }
+    let monitor = app
+        .monitor_from_point(x as f64, y as f64)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No monitor at {},{}", x, y))?;
+    let bounds = CaptureRect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    };
+
+    let rect = clamp_box_to(x, y, padding, bounds);
+    capture_region(app, rect, compression, format)
+}
+
+/// A square of `padding` on each side of (x, y), kept inside `bounds`
+fn clamp_box_to(x: i32, y: i32, padding: u32, bounds: CaptureRect) -> CaptureRect {
+    let clamp_axis = |center: i32, start: i32, length: u32| {
+        let size = (padding as i64 * 2).min(length as i64);
+        let end = start as i64 + length as i64;
+        let lo = (center as i64 - padding as i64).clamp(start as i64, end - size);
+        (lo as i32, size as u32)
+    };
+    let (x, width) = clamp_axis(x, bounds.x, bounds.width);
+    let (y, height) = clamp_axis(y, bounds.y, bounds.height);
+    CaptureRect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+/// Most formats capture_multi encodes a single frame into
+const MAX_MULTI_FORMATS: usize = 4;
+
+/// One encoding of a capture_multi frame
+#[derive(Debug, Serialize, Clone)]
+pub struct FormattedCapture {
+    pub format: CaptureFormat,
+    pub capture: CaptureResult,
+}
+
+/// Capture the primary screen once and encode that frame in each of `formats`
+///
+/// Every result shows exactly the same frame, without the latency of capturing twice.
+/// Repeated formats are only encoded once; results follow the order they were asked for.
+#[tauri::command]
+pub fn capture_multi(
+    app: AppHandle,
+    formats: Vec<CaptureFormat>,
+    compression: Option<PngCompression>,
+) -> Result<Vec<FormattedCapture>, String> {
+    let mut unique: Vec<CaptureFormat> = Vec::new();
+    for format in formats {
+        if !unique.contains(&format) {
+            unique.push(format);
+        }
+    }
+    if unique.is_empty() {
+        return Err("At least one format is required".to_string());
+    }
+    if unique.len() > MAX_MULTI_FORMATS {
+        return Err(format!(
+            "At most {} formats can be requested",
+            MAX_MULTI_FORMATS
+        ));
+    }
+
+    let frame = capture_screen(app, Some(CaptureOptions::with_compression(compression)))?;
+    unique
+        .into_iter()
+        .map(|format| {
+            let mut capture = frame.clone();
+            convert_format(&mut capture, format)?;
+            Ok(FormattedCapture { format, capture })
+        })
+        .collect()
+}
+
+/// Capture the screen and deliver it as a `capture-ready` event instead of a return value
+#[tauri::command]
+pub fn capture_and_emit(app: AppHandle, compression: Option<PngCompression>) -> Result<(), String> {
+    let result = capture_screen(
+        app.clone(),
+        Some(CaptureOptions::with_compression(compression)),
+    )?;
+    app.emit("capture-ready", result).map_err(|e| e.to_string())
+}
+
+/// Delete leftover temp captures, e.g. from a capture process killed before cleanup
+///
+/// Only files with our own prefix that are older than a minute are touched.
+pub fn sweep_temp_captures() -> usize {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return 0;
+    };
+
+    let now = SystemTime::now();
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(TEMP_CAPTURE_PREFIX) && name.ends_with(".png")
+        })
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= STALE_CAPTURE_AGE)
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+/// Manually remove leftover temp captures, returning how many were deleted
+#[tauri::command]
+pub fn cleanup_temp_captures() -> usize {
+    sweep_temp_captures()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "webp")]
+    use std::time::Instant;
+
+    fn blank_result(width: u32, height: u32) -> CaptureResult {
+        CaptureResult {
+            data: String::new(),
+            metadata: CaptureMetadata {
+                width,
+                height,
+                mime_type: "image/png".to_string(),
+                compression: None,
+                active_app: None,
+                active_window_title: None,
+                downscaled: false,
+                region: None,
+                macos_backend: None,
+                rotation: 0,
+                profile: None,
+                monitor: None,
+                thumbnail_width: None,
+                thumbnail_height: None,
+                stabilized: None,
+                has_alpha: false,
+            },
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn thumbnail_fits_within_max_side() {
+        let image = image::RgbImage::new(1920, 1080);
+        let mut result = blank_result(1920, 1080);
+        add_thumbnail(&mut result, &image).unwrap();
+
+        assert_eq!(result.metadata.thumbnail_width, Some(THUMBNAIL_MAX_SIDE));
+        assert_eq!(result.metadata.thumbnail_height, Some(144));
+        let png = BASE64_STANDARD.decode(result.thumbnail.unwrap()).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!(
+            (decoded.width(), decoded.height()),
+            (THUMBNAIL_MAX_SIDE, 144)
+        );
+    }
+
+    #[test]
+    fn small_captures_are_not_upscaled() {
+        let image = image::RgbImage::new(100, 40);
+        let mut result = blank_result(100, 40);
+        add_thumbnail(&mut result, &image).unwrap();
+
+        assert_eq!(result.metadata.thumbnail_width, Some(100));
+        assert_eq!(result.metadata.thumbnail_height, Some(40));
+    }
+
+    /// A 1920x1080 frame shaped like a desktop: flat panels, rows of glyph-like text and a
+    /// gradient standing in for a photo
+    #[cfg(feature = "webp")]
+    fn synthetic_screen() -> (u32, u32, Vec<u8>) {
+        let (width, height) = (1920u32, 1080u32);
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        let glyphs: Vec<bool> = (0..(width / 8) * height.div_ceil(16))
+            .map(|_| next() % 3 != 0)
+            .collect();
+
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = if y < 32 {
+                    [40, 40, 48]
+                } else if x >= 1280 && y >= 540 {
+                    [
+                        (x / 3 % 256) as u8,
+                        (y / 2 % 256) as u8,
+                        ((x + y) / 5 % 256) as u8,
+                    ]
+                } else if x < 240 {
+                    [232, 232, 236]
+                } else {
+                    let glyph = glyphs[((y / 16) * (width / 8) + x / 8) as usize];
+                    let ink = glyph && y % 16 > 3 && y % 16 < 13 && x % 8 > 0 && (x ^ y) % 3 != 0;
+                    if ink {
+                        [30, 30, 30]
+                    } else {
+                        [255, 255, 255]
+                    }
+                };
+                rgb.extend_from_slice(&pixel);
+            }
+        }
+        (width, height, rgb)
+    }
+
+    /// Size and encode time of WebP against PNG, as convert_format encodes them
+    ///
+    /// Run with `cargo test --release --features webp -- --ignored --nocapture webp`.
+    #[cfg(feature = "webp")]
+    #[test]
+    #[ignore]
+    fn webp_against_png() {
+        let (width, height, rgb) = synthetic_screen();
+        let mut rows = Vec::new();
+
+        for compression in [
+            PngCompression::Fast,
+            PngCompression::Default,
+            PngCompression::Best,
+        ] {
+            let start = Instant::now();
+            let png = encode_png(width, height, &rgb, compression).unwrap();
+            rows.push((format!("png {:?}", compression), png.len(), start.elapsed()));
+        }
+        let encoder = webp::Encoder::from_rgb(&rgb, width, height);
+        let start = Instant::now();
+        let lossless = encoder.encode_lossless();
+        rows.push(("webp lossless".to_string(), lossless.len(), start.elapsed()));
+        let start = Instant::now();
+        let lossy = encoder.encode(default_webp_quality() as f32);
+        rows.push(("webp q80".to_string(), lossy.len(), start.elapsed()));
+
+        for (name, size, elapsed) in &rows {
+            println!("{:<16} {:>9} bytes {:>8.1?}", name, size, elapsed);
+        }
+        // Lossy only wins on photo-like content; sharp text costs it more than PNG's deflate
+        assert!(lossless.len() < rows[1].1);
+    }
+}
@@ -0,0 +1,92 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use tauri::AppHandle;
+
+use super::{capture_screen, convert_format, encode_png, CaptureOptions, CaptureResult};
+use crate::config::{self, CaptureProfile};
+
+/// Capture the primary screen with every setting from the named capture profile
+#[tauri::command]
+pub fn capture_with_profile(app: AppHandle, name: String) -> Result<CaptureResult, String> {
+    let profile = config::load_config(&app)
+        .capture
+        .profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Capture profile {} not found", name))?;
+
+    let mut result = capture_screen(
+        app,
+        Some(CaptureOptions::with_compression(Some(profile.compression))),
+    )?;
+    if profile.max_dimension.is_some() || profile.grayscale {
+        apply_profile(&mut result, &profile)?;
+    }
+    convert_format(&mut result, profile.format)?;
+    result.metadata.profile = Some(profile.name);
+    Ok(result)
+}
+
+/// Apply a profile's size limit and grayscale, re-encoding the capture as PNG
+fn apply_profile(result: &mut CaptureResult, profile: &CaptureProfile) -> Result<(), String> {
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    let mut image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+
+    if let Some(max) = profile.max_dimension {
+        let max = max.max(1);
+        if image.width() > max || image.height() > max {
+            image = image.resize(max, max, image::imageops::FilterType::Triangle);
+            result.metadata.downscaled = true;
+        }
+    }
+    if profile.grayscale {
+        image = image.grayscale();
+    }
+
+    let rgb = image.to_rgb8();
+    let png_data = encode_png(rgb.width(), rgb.height(), rgb.as_raw(), profile.compression)?;
+    result.data = BASE64_STANDARD.encode(png_data);
+    result.metadata.width = rgb.width();
+    result.metadata.height = rgb.height();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_capture_profiles(app: AppHandle) -> Vec<CaptureProfile> {
+    config::load_config(&app).capture.profiles
+}
+
+/// Save a capture profile, replacing any profile with the same name
+#[tauri::command]
+pub fn save_capture_profile(app: AppHandle, profile: CaptureProfile) -> Result<(), String> {
+    let name = profile.name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    let profile = CaptureProfile {
+        name: name.to_string(),
+        ..profile
+    };
+
+    let mut config = config::load_config(&app);
+    let profiles = &mut config.capture.profiles;
+    match profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    config::save_config(&app, &config, "save_capture_profile")
+}
+
+#[tauri::command]
+pub fn delete_capture_profile(app: AppHandle, name: String) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    let profiles = &mut config.capture.profiles;
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Err("Capture profile not found".to_string());
+    }
+    config::save_config(&app, &config, "delete_capture_profile")
+}
@@ -0,0 +1,84 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use image::{imageops, Rgb};
+use serde::Deserialize;
+
+use super::{encode_png, CaptureResult, PngCompression};
+
+/// How a redacted rectangle is obscured
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum RedactionMode {
+    /// Solid black
+    #[default]
+    Fill,
+    /// A heavy blur, strong enough that text underneath can't be read back
+    Blur,
+}
+
+/// A rectangle of the captured image to obscure, in image pixels
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Redaction {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub mode: RedactionMode,
+}
+
+/// Obscure each redaction in a capture, re-encoding it as PNG
+///
+/// Every rectangle must lie fully inside the image; nothing is redacted otherwise.
+pub fn apply_redactions(
+    result: &mut CaptureResult,
+    redactions: &[Redaction],
+    compression: PngCompression,
+) -> Result<(), String> {
+    let (width, height) = (result.metadata.width, result.metadata.height);
+    for r in redactions {
+        let inside = r.width > 0
+            && r.height > 0
+            && r.x.checked_add(r.width).is_some_and(|right| right <= width)
+            && r.y
+                .checked_add(r.height)
+                .is_some_and(|bottom| bottom <= height);
+        if !inside {
+            return Err(format!(
+                "Redaction {}x{} at ({}, {}) is outside the {}x{} capture",
+                r.width, r.height, r.x, r.y, width, height
+            ));
+        }
+    }
+
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    let mut image = image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgb8();
+
+    for r in redactions {
+        match r.mode {
+            RedactionMode::Fill => {
+                for y in r.y..r.y + r.height {
+                    for x in r.x..r.x + r.width {
+                        image.put_pixel(x, y, Rgb([0, 0, 0]));
+                    }
+                }
+            }
+            RedactionMode::Blur => {
+                let region = imageops::crop_imm(&image, r.x, r.y, r.width, r.height).to_image();
+                // Scale the blur with the region so large text is smeared as much as small
+                let sigma = (r.width.max(r.height) as f32 / 4.0).max(8.0);
+                let blurred = imageops::fast_blur(&region, sigma);
+                imageops::replace(&mut image, &blurred, r.x as i64, r.y as i64);
+            }
+        }
+    }
+
+    let png_data = encode_png(image.width(), image.height(), image.as_raw(), compression)?;
+    result.data = BASE64_STANDARD.encode(png_data);
+    result.metadata.mime_type = "image/png".to_string();
+    result.metadata.compression = Some(compression);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::diff::{changed_fraction, preview_grid};
+use super::{capture_screen, platform, CaptureOptions, CaptureResult, PngCompression};
+
+const MAX_STABLE_TIMEOUT_MS: u64 = 10_000;
+/// Time between the cheap frames compared while waiting
+const STABLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Share of the screen that may still change between two frames for it to count as still
+const MOTION_THRESHOLD: f64 = 0.005;
+
+/// Wait for the screen to stop moving, then capture it
+///
+/// Frames are compared as small grids from low-resolution captures, so nothing is encoded
+/// until the screen holds still, e.g. after a scroll or transition ends. After `timeout_ms`
+/// it captures anyway; metadata.stabilized tells the two apart.
+#[tauri::command]
+pub async fn capture_when_stable(
+    app: AppHandle,
+    timeout_ms: u64,
+    compression: Option<PngCompression>,
+) -> Result<CaptureResult, String> {
+    if timeout_ms > MAX_STABLE_TIMEOUT_MS {
+        return Err(format!(
+            "timeout_ms must be at most {}",
+            MAX_STABLE_TIMEOUT_MS
+        ));
+    }
+    if !platform::can_capture() {
+        return Err("Screen capture permission has not been granted".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let stabilized = wait_until_stable(Duration::from_millis(timeout_ms));
+        let mut result = capture_screen(app, Some(CaptureOptions::with_compression(compression)))?;
+        result.metadata.stabilized = Some(stabilized);
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Poll until two consecutive frames barely differ, returning false on timeout
+fn wait_until_stable(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut previous = None;
+
+    loop {
+        if let Some(grid) = preview_grid() {
+            if previous
+                .as_ref()
+                .is_some_and(|previous| changed_fraction(previous, &grid) <= MOTION_THRESHOLD)
+            {
+                return true;
+            }
+            previous = Some(grid);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(STABLE_POLL_INTERVAL.min(deadline - now));
+    }
+}
@@ -0,0 +1,137 @@
+use super::{capture_screen, CaptureOptions, CaptureResult, PngCompression};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const MAX_STREAM_FPS: u32 = 30;
+const MAX_STREAM_DURATION_MS: u32 = 5 * 60 * 1000;
+
+/// The running capture stream, if any
+#[derive(Default)]
+pub struct CaptureStreamState {
+    /// Generation of the running stream, or 0; each stream only runs while this is its own,
+    /// so one stopped mid-sleep can't outlive or end a restart
+    active: AtomicU64,
+    generation: AtomicU64,
+}
+
+impl CaptureStreamState {
+    /// Stop the running stream, returning whether there was one
+    pub fn stop(&self) -> bool {
+        self.active.swap(0, Ordering::SeqCst) != 0
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CaptureFrame {
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub capture: CaptureResult,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CaptureStreamEnded {
+    pub frames: u64,
+    /// Frame slots skipped because capture/encoding couldn't keep up
+    pub dropped_frames: u64,
+    pub error: Option<String>,
+}
+
+/// Capture frames at `fps` for `duration_ms`, emitting each as a `capture-frame` event
+///
+/// Ends with a `capture-stream-ended` event, either when the duration elapses or after
+/// stop_capture_stream.
+#[tauri::command]
+pub fn start_capture_stream(
+    app: AppHandle,
+    state: State<'_, CaptureStreamState>,
+    fps: u32,
+    duration_ms: u32,
+) -> Result<(), String> {
+    if fps == 0 || fps > MAX_STREAM_FPS {
+        return Err(format!("fps must be between 1 and {}", MAX_STREAM_FPS));
+    }
+    if duration_ms == 0 || duration_ms > MAX_STREAM_DURATION_MS {
+        return Err(format!(
+            "duration_ms must be between 1 and {}",
+            MAX_STREAM_DURATION_MS
+        ));
+    }
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if state
+        .active
+        .compare_exchange(0, generation, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("A capture stream is already running".to_string());
+    }
+
+    let interval = Duration::from_secs_f64(1.0 / fps as f64);
+    let duration = Duration::from_millis(duration_ms as u64);
+    crate::power::begin_stream_keep_awake(&app);
+    std::thread::spawn(move || run_stream(app, interval, duration, generation));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_capture_stream(state: State<'_, CaptureStreamState>) {
+    state.stop();
+}
+
+fn run_stream(app: AppHandle, interval: Duration, duration: Duration, generation: u64) {
+    let state = app.state::<CaptureStreamState>();
+    let deadline = Instant::now() + duration;
+    let mut next_frame = Instant::now();
+    let mut frames = 0;
+    let mut dropped_frames = 0;
+    let mut error = None;
+
+    while state.active.load(Ordering::SeqCst) == generation && Instant::now() < deadline {
+        let now = Instant::now();
+        if now < next_frame {
+            // Sleep in short slices so a stop request is picked up promptly
+            std::thread::sleep((next_frame - now).min(Duration::from_millis(50)));
+            continue;
+        }
+
+        // Skip any slots we fell behind on instead of bursting to catch up
+        let behind = ((now - next_frame).as_secs_f64() / interval.as_secs_f64()) as u32;
+        dropped_frames += behind as u64;
+        next_frame += interval * (behind + 1);
+
+        match capture_screen(
+            app.clone(),
+            Some(CaptureOptions::with_compression(Some(PngCompression::Fast))),
+        ) {
+            Ok(capture) => {
+                let _ = app.emit(
+                    "capture-frame",
+                    CaptureFrame {
+                        sequence: frames,
+                        capture,
+                    },
+                );
+                frames += 1;
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    // Only clear our own run; a newer stream may already have started
+    let _ = state
+        .active
+        .compare_exchange(generation, 0, Ordering::SeqCst, Ordering::SeqCst);
+    crate::power::end_stream_keep_awake(&app);
+    let _ = app.emit(
+        "capture-stream-ended",
+        CaptureStreamEnded {
+            frames,
+            dropped_frames,
+            error,
+        },
+    );
+}
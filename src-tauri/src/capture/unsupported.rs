@@ -1,5 +1,65 @@
+use crate::config::MacosCaptureBackend;
+
+use super::{ActiveApp, CaptureRect, CaptureResult, PngCompression, RawCapture, RgbaMode};
+
 /// Fallback capture_screen for unsupported platforms
-#[tauri::command]
-pub fn capture_screen() -> Result<String, String> {
+pub fn capture_screen(
+    _compression: PngCompression,
+    _backend: MacosCaptureBackend,
+    _alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
     Err("Screen capture is not supported on this platform".to_string())
 }
+
+/// Fallback capture_region for unsupported platforms
+pub fn capture_region(
+    _rect: CaptureRect,
+    _scale_factor: f64,
+    _compression: PngCompression,
+    _backend: MacosCaptureBackend,
+    _alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    Err("Screen capture is not supported on this platform".to_string())
+}
+
+/// Fallback capture_raw for unsupported platforms
+pub fn capture_raw(_backend: MacosCaptureBackend) -> Result<RawCapture, String> {
+    Err("Screen capture is not supported on this platform".to_string())
+}
+
+pub fn capture_preview() -> Result<RawCapture, String> {
+    Err("Screen capture is not supported on this platform".to_string())
+}
+
+/// The frontmost app isn't known on unsupported platforms
+pub fn active_app() -> ActiveApp {
+    ActiveApp::default()
+}
+
+/// The focused monitor isn't known on unsupported platforms
+pub fn active_monitor_point() -> Option<(f64, f64)> {
+    None
+}
+
+/// Capture isn't available on unsupported platforms
+pub fn capture_screen_excluding(
+    _pids: &[u32],
+    _compression: PngCompression,
+) -> Result<CaptureResult, String> {
+    Err("Screen capture is not supported on this platform".to_string())
+}
+
+/// Processes can't be looked up on unsupported platforms
+pub fn process_exists(_pid: u32) -> bool {
+    false
+}
+
+/// Running apps aren't listed on unsupported platforms
+pub fn running_app_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// Capture is never possible on unsupported platforms
+pub fn can_capture() -> bool {
+    false
+}
@@ -0,0 +1,146 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::{capture_screen, CaptureOptions, PngCompression};
+use crate::config;
+
+/// Longest response body returned to the caller; anything past it is cut off
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const MULTIPART_BOUNDARY: &str = "----LensCaptureBoundary7d1f0c";
+
+/// What the upload endpoint answered
+#[derive(Debug, Serialize, Clone)]
+pub struct UploadResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Why capture_and_upload failed, so network trouble isn't mistaken for a capture problem
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", content = "message")]
+pub enum UploadError {
+    /// Uploading is off or the request doesn't match the configured endpoint
+    Config(String),
+    Capture(String),
+    /// The request couldn't be sent or its response couldn't be read
+    Network(String),
+}
+
+/// Set the endpoint capture_and_upload may send captures to, or None to disable uploading
+#[tauri::command]
+pub fn set_upload_endpoint(app: AppHandle, url: Option<String>) -> Result<(), String> {
+    if let Some(url) = &url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("The upload URL must be http or https".to_string());
+        }
+    }
+    let mut config = config::load_config(&app);
+    config.upload.url = url;
+    config::save_config(&app, &config, "set_upload_endpoint")
+}
+
+/// Capture the primary screen and POST it to the configured upload endpoint
+///
+/// Nothing is sent until an endpoint is set with set_upload_endpoint, and only ever to that
+/// endpoint: `url` may be omitted, but if given it has to match. The image goes as the raw
+/// request body, or with `multipart` as a form field named "file". The configured timeout
+/// and size limit apply; a non-2xx status is still returned as a response, not an error.
+#[tauri::command]
+pub async fn capture_and_upload(
+    app: AppHandle,
+    url: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    multipart: Option<bool>,
+    compression: Option<PngCompression>,
+) -> Result<UploadResponse, UploadError> {
+    let settings = config::load_config(&app).upload;
+    let Some(endpoint) = settings.url else {
+        return Err(UploadError::Config(
+            "No upload URL is configured".to_string(),
+        ));
+    };
+    if url.is_some_and(|url| url != endpoint) {
+        return Err(UploadError::Config(
+            "Captures can only be uploaded to the configured upload URL".to_string(),
+        ));
+    }
+
+    let capture = tauri::async_runtime::spawn_blocking(move || {
+        capture_screen(app, Some(CaptureOptions::with_compression(compression)))
+    })
+    .await
+    .map_err(|e| UploadError::Capture(e.to_string()))?
+    .map_err(UploadError::Capture)?;
+    let image = BASE64_STANDARD
+        .decode(&capture.data)
+        .map_err(|e| UploadError::Capture(e.to_string()))?;
+    if image.len() as u64 > settings.max_bytes {
+        return Err(UploadError::Capture(format!(
+            "Capture of {} bytes exceeds the {} byte upload limit",
+            image.len(),
+            settings.max_bytes
+        )));
+    }
+
+    let mime_type = capture.metadata.mime_type;
+    let (content_type, body) = if multipart.unwrap_or(false) {
+        (
+            format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+            multipart_body(&image, &mime_type),
+        )
+    } else {
+        (mime_type, image)
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(settings.timeout_ms))
+        .build()
+        .map_err(|e| UploadError::Network(e.to_string()))?;
+    let mut request = client
+        .post(&endpoint)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .body(body);
+    for (name, value) in headers.unwrap_or_default() {
+        request = request.header(name, value);
+    }
+
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| UploadError::Network(e.to_string()))?;
+    let status = response.status().as_u16();
+
+    // Read chunk by chunk so an oversized response is never buffered in full
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| UploadError::Network(e.to_string()))?
+    {
+        let room = MAX_RESPONSE_BYTES - bytes.len();
+        bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        if bytes.len() == MAX_RESPONSE_BYTES {
+            break;
+        }
+    }
+    let body = String::from_utf8_lossy(&bytes).into_owned();
+    Ok(UploadResponse { status, body })
+}
+
+/// A multipart/form-data body with the image as its only field, "file"
+fn multipart_body(image: &[u8], mime_type: &str) -> Vec<u8> {
+    let extension = mime_type.rsplit('/').next().unwrap_or("png");
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"capture.{}\"\r\nContent-Type: {}\r\n\r\n",
+        MULTIPART_BOUNDARY, extension, mime_type
+    )
+    .into_bytes();
+    body.extend_from_slice(image);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+    body
+}
@@ -0,0 +1,103 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::diff::{changed_fraction, preview_grid};
+use super::platform;
+
+/// Shortest polling interval, so watching can't turn into a capture stream
+const MIN_WATCH_INTERVAL_MS: u64 = 250;
+
+/// Set while the screen watcher runs; clearing `running` stops the watcher
+#[derive(Default)]
+pub struct ScreenWatchState {
+    running: AtomicBool,
+    /// Bumped on every start, so a watcher stopped mid-sleep can't outlive a restart
+    generation: AtomicU64,
+    /// interval_ms and threshold of the running watcher, so a pause can restart it
+    settings: Mutex<Option<(u64, f64)>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScreenChanged {
+    /// Share of the screen that changed since the previous check, from 0.0 to 1.0
+    pub changed_fraction: f64,
+}
+
+/// Check the screen every `interval_ms` and emit `screen-changed` when more than
+/// `threshold` (0.0 to 1.0) of it changed since the last check
+///
+/// Each check is a low-resolution frame shrunk to a small grid, so nothing is PNG-encoded. Refuses
+/// to start without screen capture permission; a check that fails is skipped.
+#[tauri::command]
+pub fn start_screen_watch(
+    app: AppHandle,
+    state: State<'_, ScreenWatchState>,
+    interval_ms: u64,
+    threshold: f64,
+) -> Result<(), String> {
+    if interval_ms < MIN_WATCH_INTERVAL_MS {
+        return Err(format!(
+            "interval_ms must be at least {}",
+            MIN_WATCH_INTERVAL_MS
+        ));
+    }
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("threshold must be between 0.0 and 1.0".to_string());
+    }
+    if !platform::can_capture() {
+        return Err("Screen capture permission has not been granted".to_string());
+    }
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("The screen watcher is already running".to_string());
+    }
+    *state.settings.lock().unwrap() = Some((interval_ms, threshold));
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let interval = Duration::from_millis(interval_ms);
+    std::thread::spawn(move || run_watch(app, interval, threshold, generation));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_screen_watch(state: State<'_, ScreenWatchState>) {
+    state.running.store(false, Ordering::SeqCst);
+}
+
+/// Stop the screen watcher, returning its interval_ms and threshold if it was running
+pub fn pause_screen_watch(app: &AppHandle) -> Option<(u64, f64)> {
+    let state = app.state::<ScreenWatchState>();
+    if !state.running.swap(false, Ordering::SeqCst) {
+        return None;
+    }
+    let settings = *state.settings.lock().unwrap();
+    settings
+}
+
+fn run_watch(app: AppHandle, interval: Duration, threshold: f64, generation: u64) {
+    let state = app.state::<ScreenWatchState>();
+    let mut previous = None;
+
+    while state.running.load(Ordering::SeqCst)
+        && state.generation.load(Ordering::SeqCst) == generation
+    {
+        if let Some(grid) = preview_grid() {
+            if let Some(previous) = &previous {
+                let changed = changed_fraction(previous, &grid);
+                if changed > threshold {
+                    let _ = app.emit(
+                        "screen-changed",
+                        ScreenChanged {
+                            changed_fraction: changed,
+                        },
+                    );
+                }
+            }
+            previous = Some(grid);
+        }
+
+        std::thread::sleep(interval);
+    }
+}
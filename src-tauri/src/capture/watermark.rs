@@ -0,0 +1,144 @@
+use ab_glyph::{FontVec, PxScale};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::{draw_text_mut, text_size};
+use serde::Deserialize;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use super::{encode_png, CaptureResult, PngCompression};
+
+/// Bundled font used for watermark text, relative to the resource dir
+const WATERMARK_FONT: &str = "resources/fonts/DejaVuSans.ttf";
+
+/// Text height and margin from the image edge, in logical pixels
+const WATERMARK_TEXT_SIZE: f32 = 14.0;
+const WATERMARK_MARGIN: f32 = 12.0;
+
+/// Corner of the capture the watermark is drawn in
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub enum WatermarkCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Text burned into a capture, e.g. a timestamp for documentation screenshots
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatermarkOptions {
+    pub text: String,
+    #[serde(default)]
+    pub corner: WatermarkCorner,
+    /// 0.0 (invisible) to 1.0 (opaque)
+    #[serde(default = "default_watermark_opacity")]
+    pub opacity: f32,
+}
+
+fn default_watermark_opacity() -> f32 {
+    0.8
+}
+
+/// Draw the watermark onto a capture and re-encode it
+///
+/// Text is sized by the primary monitor's scale factor so it stays legible on HiDPI
+/// captures. A dark shadow behind the white text keeps it readable on any background.
+pub fn apply_watermark(
+    app: &AppHandle,
+    result: &mut CaptureResult,
+    options: &WatermarkOptions,
+    compression: PngCompression,
+) -> Result<(), String> {
+    let font = load_font(app)?;
+    let scale_factor = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| m.scale_factor() as f32)
+        .unwrap_or(1.0);
+
+    let bytes = BASE64_STANDARD
+        .decode(&result.data)
+        .map_err(|e| e.to_string())?;
+    let mut image = image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+
+    let scale = PxScale::from(WATERMARK_TEXT_SIZE * scale_factor);
+    let shadow_offset = scale_factor.round().max(1.0) as i32;
+    let (text_width, text_height) = text_size(scale, &font, &options.text);
+
+    // Render into a transparent layer first so opacity can be applied when compositing
+    let mut layer = RgbaImage::new(
+        text_width + shadow_offset as u32,
+        text_height + shadow_offset as u32,
+    );
+    draw_text_mut(
+        &mut layer,
+        Rgba([0, 0, 0, 255]),
+        shadow_offset,
+        shadow_offset,
+        scale,
+        &font,
+        &options.text,
+    );
+    draw_text_mut(
+        &mut layer,
+        Rgba([255, 255, 255, 255]),
+        0,
+        0,
+        scale,
+        &font,
+        &options.text,
+    );
+
+    let margin = (WATERMARK_MARGIN * scale_factor) as i64;
+    let (image_width, image_height) = (image.width() as i64, image.height() as i64);
+    let (layer_width, layer_height) = (layer.width() as i64, layer.height() as i64);
+    let x = match options.corner {
+        WatermarkCorner::TopLeft | WatermarkCorner::BottomLeft => margin,
+        WatermarkCorner::TopRight | WatermarkCorner::BottomRight => {
+            image_width - layer_width - margin
+        }
+    };
+    let y = match options.corner {
+        WatermarkCorner::TopLeft | WatermarkCorner::TopRight => margin,
+        WatermarkCorner::BottomLeft | WatermarkCorner::BottomRight => {
+            image_height - layer_height - margin
+        }
+    };
+
+    let opacity = options.opacity.clamp(0.0, 1.0);
+    for (lx, ly, src) in layer.enumerate_pixels() {
+        let (ix, iy) = (x + lx as i64, y + ly as i64);
+        if ix < 0 || iy < 0 || ix >= image_width || iy >= image_height {
+            continue;
+        }
+        let alpha = src[3] as f32 / 255.0 * opacity;
+        if alpha == 0.0 {
+            continue;
+        }
+        let dst = image.get_pixel_mut(ix as u32, iy as u32);
+        for c in 0..3 {
+            dst[c] = (src[c] as f32 * alpha + dst[c] as f32 * (1.0 - alpha)).round() as u8;
+        }
+    }
+
+    let rgb = image::DynamicImage::ImageRgba8(image).into_rgb8();
+    let png_data = encode_png(rgb.width(), rgb.height(), rgb.as_raw(), compression)?;
+    result.data = BASE64_STANDARD.encode(png_data);
+    result.metadata.mime_type = "image/png".to_string();
+    result.metadata.compression = Some(compression);
+    Ok(())
+}
+
+fn load_font(app: &AppHandle) -> Result<FontVec, String> {
+    let path = app
+        .path()
+        .resolve(WATERMARK_FONT, BaseDirectory::Resource)
+        .map_err(|e| format!("Failed to locate watermark font: {}", e))?;
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to load watermark font: {}", e))?;
+    FontVec::try_from_vec(data).map_err(|e| format!("Failed to load watermark font: {}", e))
+}
@@ -1,31 +1,396 @@
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 
+use crate::config::MacosCaptureBackend;
+
+use super::buffers::{self, BufferKey};
+use super::{
+    encode_png, ActiveApp, CaptureMetadata, CaptureRect, CaptureResult, PngCompression, RawCapture,
+    RgbaMode,
+};
+
 /// Capture screen on Windows using GDI
-#[tauri::command]
-pub fn capture_screen() -> Result<String, String> {
+///
+/// GDI has no alpha, so RgbaMode::PreserveAlpha falls back to an opaque capture.
+pub fn capture_screen(
+    compression: PngCompression,
+    _backend: MacosCaptureBackend,
+    _alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    let monitor = primary_monitor_rect()?;
+    let mut capture = capture_rect(
+        monitor.x,
+        monitor.y,
+        monitor.width as i32,
+        monitor.height as i32,
+        compression,
+    )?;
+    capture.metadata.monitor = Some(monitor);
+    Ok(capture)
+}
+
+/// The primary monitor's bounds in virtual desktop coordinates
+///
+/// Looked up rather than assumed to be SM_CXSCREEN x SM_CYSCREEN at the origin, so
+/// captures keep targeting the primary monitor whatever the layout around it.
+fn primary_monitor_rect() -> Result<CaptureRect, String> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTOPRIMARY,
+    };
+
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        let monitor = MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY);
+        if monitor.is_invalid() || !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return Err("Failed to get the primary monitor".to_string());
+        }
+    }
+    let rect = info.rcMonitor;
+    if rect.right <= rect.left || rect.bottom <= rect.top {
+        return Err("Failed to get screen dimensions".to_string());
+    }
+    Ok(CaptureRect {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left) as u32,
+        height: (rect.bottom - rect.top) as u32,
+    })
+}
+
+/// Capture a rectangle of the virtual desktop, in physical pixels
+pub fn capture_region(
+    rect: CaptureRect,
+    _scale_factor: f64,
+    compression: PngCompression,
+    _backend: MacosCaptureBackend,
+    _alpha: RgbaMode,
+) -> Result<CaptureResult, String> {
+    capture_rect(
+        rect.x,
+        rect.y,
+        rect.width as i32,
+        rect.height as i32,
+        compression,
+    )
+}
+
+fn capture_rect(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    compression: PngCompression,
+) -> Result<CaptureResult, String> {
+    let key = BufferKey {
+        x,
+        y,
+        width: width as u32,
+        height: height as u32,
+    };
+    let pixels = grab_rgb(key)?;
+    let (data, mime_type, compression) =
+        match encode_png(width as u32, height as u32, &pixels, compression) {
+            Ok(png_data) => (png_data, "image/png", Some(compression)),
+            Err(e) => {
+                eprintln!("Warning: PNG encoding failed ({}), falling back to BMP", e);
+                let bmp_data = encode_bmp(width as u32, height as u32, &pixels)?;
+                (bmp_data, "image/bmp", None)
+            }
+        };
+    buffers::give_back(key, pixels);
+
+    Ok(CaptureResult {
+        data: BASE64_STANDARD.encode(data),
+        metadata: CaptureMetadata {
+            width: width as u32,
+            height: height as u32,
+            mime_type: mime_type.to_string(),
+            compression,
+            active_app: None,
+            active_window_title: None,
+            downscaled: false,
+            region: None,
+            macos_backend: None,
+            rotation: 0,
+            profile: None,
+            monitor: None,
+            thumbnail_width: None,
+            thumbnail_height: None,
+            stabilized: None,
+            has_alpha: false,
+        },
+        thumbnail: None,
+    })
+}
+
+/// Encode tightly packed 8-bit RGB pixels as an uncompressed BMP
+///
+/// Only used as a fallback when PNG encoding fails, so a capture is never lost to it.
+fn encode_bmp(width: u32, height: u32, rgb: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bmp_data: Vec<u8> = Vec::new();
+    image::codecs::bmp::BmpEncoder::new(&mut bmp_data)
+        .encode(rgb, width, height, image::ExtendedColorType::Rgb8)
+        .map_err(|e| e.to_string())?;
+    Ok(bmp_data)
+}
+
+/// Capture the primary screen with the top-level windows of `pids` left out
+///
+/// Each window is excluded with WDA_EXCLUDEFROMCAPTURE for the duration of the capture,
+/// then its previous affinity is restored, even if the capture fails. Windows only lets a
+/// process set the affinity of its own windows, so any window that refuses is blacked out
+/// in the image instead, over its on-screen rectangle.
+pub fn capture_screen_excluding(
+    pids: &[u32],
+    compression: PngCompression,
+) -> Result<CaptureResult, String> {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowDisplayAffinity, GetWindowRect, IsWindowVisible, SetWindowDisplayAffinity,
+        WDA_EXCLUDEFROMCAPTURE, WINDOW_DISPLAY_AFFINITY,
+    };
+
+    /// Puts excluded windows' affinities back when dropped
+    struct RestoreAffinity(Vec<(HWND, u32)>);
+
+    impl Drop for RestoreAffinity {
+        fn drop(&mut self) {
+            for (hwnd, affinity) in &self.0 {
+                unsafe {
+                    let _ = SetWindowDisplayAffinity(*hwnd, WINDOW_DISPLAY_AFFINITY(*affinity));
+                }
+            }
+        }
+    }
+
+    let monitor = primary_monitor_rect()?;
+    let (width, height) = (monitor.width as i32, monitor.height as i32);
+
+    let mut restore = RestoreAffinity(Vec::new());
+    let mut blackouts: Vec<RECT> = Vec::new();
+    for hwnd in top_level_windows() {
+        let mut pid = 0u32;
+        unsafe {
+            windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            if !pids.contains(&pid) || !IsWindowVisible(hwnd).as_bool() {
+                continue;
+            }
+            let mut previous = 0u32;
+            if GetWindowDisplayAffinity(hwnd, &mut previous).is_ok()
+                && SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE).is_ok()
+            {
+                restore.0.push((hwnd, previous));
+                continue;
+            }
+            let mut rect = RECT::default();
+            if GetWindowRect(hwnd, &mut rect).is_ok() {
+                blackouts.push(rect);
+            }
+        }
+    }
+
+    let key = BufferKey {
+        x: monitor.x,
+        y: monitor.y,
+        width: monitor.width,
+        height: monitor.height,
+    };
+    let pixels = grab_rgb(key);
+    drop(restore);
+    let mut pixels = pixels?;
+
+    for rect in blackouts {
+        let clamp_x = |x: i32| (x - monitor.x).clamp(0, width);
+        let clamp_y = |y: i32| (y - monitor.y).clamp(0, height);
+        let (left, right) = (clamp_x(rect.left), clamp_x(rect.right));
+        let (top, bottom) = (clamp_y(rect.top), clamp_y(rect.bottom));
+        for y in top..bottom {
+            let row = (y * width) as usize * 3;
+            pixels[row + left as usize * 3..row + right as usize * 3].fill(0);
+        }
+    }
+
+    let png_data = encode_png(width as u32, height as u32, &pixels, compression);
+    buffers::give_back(key, pixels);
+    Ok(CaptureResult {
+        data: BASE64_STANDARD.encode(png_data?),
+        metadata: CaptureMetadata {
+            width: width as u32,
+            height: height as u32,
+            mime_type: "image/png".to_string(),
+            compression: Some(compression),
+            active_app: None,
+            active_window_title: None,
+            downscaled: false,
+            region: None,
+            macos_backend: None,
+            rotation: 0,
+            profile: None,
+            monitor: Some(monitor),
+            thumbnail_width: None,
+            thumbnail_height: None,
+            stabilized: None,
+            has_alpha: false,
+        },
+        thumbnail: None,
+    })
+}
+
+/// Whether a process with this pid is running
+pub fn process_exists(pid: u32) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(process) => {
+                let _ = CloseHandle(process);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Capture the primary screen as unencoded RGBA
+pub fn capture_raw(_backend: MacosCaptureBackend) -> Result<RawCapture, String> {
+    let monitor = primary_monitor_rect()?;
+    let (width, height) = (monitor.width, monitor.height);
+    let key = BufferKey {
+        x: monitor.x,
+        y: monitor.y,
+        width,
+        height,
+    };
+    let rgb = grab_rgb(key)?;
+    let data = rgb
+        .chunks_exact(3)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+        .collect();
+    buffers::give_back(key, rgb);
+    Ok(RawCapture {
+        width,
+        height,
+        channels: 4,
+        data,
+    })
+}
+
+/// Longest side of the frames capture_preview returns
+const PREVIEW_MAX_SIDE: u32 = 256;
+
+/// A small frame of the primary monitor for change detection
+///
+/// GDI shrinks it while copying, so only a few hundred KB of pixels ever leave the screen
+/// per check. The buffer cache is left alone, so polling doesn't evict capture buffers.
+pub fn capture_preview() -> Result<RawCapture, String> {
     use windows::Win32::Foundation::HWND;
     use windows::Win32::Graphics::Gdi::{
-        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject,
-        GetDC, GetDIBits, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER,
-        BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+        ReleaseDC, SelectObject, SetStretchBltMode, StretchBlt, BITMAPINFO, BITMAPINFOHEADER,
+        BI_RGB, DIB_RGB_COLORS, HALFTONE, SRCCOPY,
     };
-    use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+    let monitor = primary_monitor_rect()?;
+    let scale = (PREVIEW_MAX_SIDE as f64 / monitor.width.max(monitor.height) as f64).min(1.0);
+    let width = ((monitor.width as f64 * scale).round() as i32).max(1);
+    let height = ((monitor.height as f64 * scale).round() as i32).max(1);
 
     unsafe {
-        let width = GetSystemMetrics(SM_CXSCREEN);
-        let height = GetSystemMetrics(SM_CYSCREEN);
+        let screen_dc = GetDC(HWND::default());
+        let mem_dc = CreateCompatibleDC(screen_dc);
+        let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+        let old_bitmap = SelectObject(mem_dc, bitmap);
+
+        SetStretchBltMode(mem_dc, HALFTONE);
+        let copied = StretchBlt(
+            mem_dc,
+            0,
+            0,
+            width,
+            height,
+            screen_dc,
+            monitor.x,
+            monitor.y,
+            monitor.width as i32,
+            monitor.height as i32,
+            SRCCOPY,
+        )
+        .as_bool();
+
+        // 32 bits per pixel, so rows need no padding
+        let mut bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            bmiColors: [Default::default()],
+        };
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        let rows = if copied {
+            GetDIBits(
+                mem_dc,
+                bitmap,
+                0,
+                height as u32,
+                Some(data.as_mut_ptr() as *mut _),
+                &mut bmi,
+                DIB_RGB_COLORS,
+            )
+        } else {
+            0
+        };
+
+        SelectObject(mem_dc, old_bitmap);
+        let _ = DeleteObject(bitmap);
+        let _ = DeleteDC(mem_dc);
+        let _ = ReleaseDC(HWND::default(), screen_dc);
 
-        if width <= 0 || height <= 0 {
-            return Err("Failed to get screen dimensions".to_string());
+        if rows != height {
+            return Err("Preview capture failed".to_string());
+        }
+        // BGRX to RGBA
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
         }
+        Ok(RawCapture {
+            width: width as u32,
+            height: height as u32,
+            channels: 4,
+            data,
+        })
+    }
+}
+
+/// Copy a rectangle of the virtual desktop into tightly packed RGB with GDI
+///
+/// The buffer comes from the capture buffer cache; hand it back once it's been used.
+fn grab_rgb(key: BufferKey) -> Result<Vec<u8>, String> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC,
+        GetDIBits, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+        SRCCOPY,
+    };
 
+    let (x, y, width, height) = (key.x, key.y, key.width as i32, key.height as i32);
+    unsafe {
         let screen_dc = GetDC(HWND::default());
         let mem_dc = CreateCompatibleDC(screen_dc);
         let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
         let old_bitmap = SelectObject(mem_dc, bitmap);
 
-        let blt_result = BitBlt(mem_dc, 0, 0, width, height, screen_dc, 0, 0, SRCCOPY);
+        let blt_result = BitBlt(mem_dc, 0, 0, width, height, screen_dc, x, y, SRCCOPY);
         if blt_result.is_err() {
             SelectObject(mem_dc, old_bitmap);
             let _ = DeleteObject(bitmap);
@@ -55,7 +420,7 @@ pub fn capture_screen() -> Result<String, String> {
         // Calculate row size with padding (rows must be 4-byte aligned)
         let row_size = ((width * 3 + 3) / 4) * 4;
         let image_size = (row_size * height) as usize;
-        let mut pixels: Vec<u8> = vec![0; image_size];
+        let mut pixels = buffers::take(key, image_size);
 
         GetDIBits(
             mem_dc,
@@ -73,29 +438,163 @@ pub fn capture_screen() -> Result<String, String> {
         let _ = DeleteDC(mem_dc);
         let _ = ReleaseDC(HWND::default(), screen_dc);
 
-        // Convert BGR to RGB
+        // Remove padding from rows in place; each row only moves towards the start
+        let packed_row = (width * 3) as usize;
+        for row in 0..height as usize {
+            let start = row * row_size as usize;
+            pixels.copy_within(start..start + packed_row, row * packed_row);
+        }
+        pixels.truncate(packed_row * height as usize);
+
+        // Convert BGR to RGB, after unpadding so padding bytes don't shift the channels
         for chunk in pixels.chunks_exact_mut(3) {
             chunk.swap(0, 2);
         }
 
-        // Remove padding from rows
-        let mut unpadded_pixels: Vec<u8> = Vec::with_capacity((width * 3 * height) as usize);
-        for row in 0..height {
-            let start = (row * row_size) as usize;
-            let end = start + (width * 3) as usize;
-            unpadded_pixels.extend_from_slice(&pixels[start..end]);
-        }
+        Ok(pixels)
+    }
+}
 
-        // Encode as PNG in memory
-        let mut png_data: Vec<u8> = Vec::new();
+/// Find the foreground app and its window title
+///
+/// When our own overlay is in the foreground, the next visible top-level window behind it
+/// in z-order is reported instead.
+pub fn active_app() -> ActiveApp {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindow, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+        GW_HWNDNEXT, GW_OWNER,
+    };
+
+    unsafe {
+        let own_pid = std::process::id();
+        let window_pid = |hwnd: HWND| {
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            pid
+        };
+
+        let mut hwnd = GetForegroundWindow();
+        while !hwnd.is_invalid()
+            && (window_pid(hwnd) == own_pid
+                || !IsWindowVisible(hwnd).as_bool()
+                || GetWindow(hwnd, GW_OWNER).is_ok_and(|owner| !owner.is_invalid()))
         {
-            let mut encoder = png::Encoder::new(&mut png_data, width as u32, height as u32);
-            encoder.set_color(png::ColorType::Rgb);
-            encoder.set_depth(png::BitDepth::Eight);
-            let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
-            writer.write_image_data(&unpadded_pixels).map_err(|e| e.to_string())?;
+            hwnd = GetWindow(hwnd, GW_HWNDNEXT).unwrap_or_default();
         }
+        if hwnd.is_invalid() {
+            return ActiveApp::default();
+        }
+
+        let mut title = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut title) as usize;
+        let window_title = (len > 0).then(|| String::from_utf16_lossy(&title[..len]));
 
-        Ok(BASE64_STANDARD.encode(png_data))
+        let name = process_name(window_pid(hwnd));
+
+        ActiveApp { name, window_title }
+    }
+}
+
+/// Executable name of a process, without its extension
+fn process_name(pid: u32) -> Option<String> {
+    use windows::Win32::Foundation::{CloseHandle, MAX_PATH};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut path = [0u16; MAX_PATH as usize];
+        let mut size = path.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            windows::core::PWSTR(path.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+        let path = String::from_utf16_lossy(&path[..size as usize]);
+        std::path::Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+    }
+}
+
+/// Every top-level window, front to back
+fn top_level_windows() -> Vec<windows::Win32::Foundation::HWND> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::EnumWindows;
+
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+        windows.push(hwnd);
+        true.into()
+    }
+
+    let mut windows: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(
+            Some(collect),
+            LPARAM(&mut windows as *mut Vec<HWND> as isize),
+        );
     }
+    windows
+}
+
+/// Names of the processes that own top-level windows
+pub fn running_app_names() -> Vec<String> {
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    let mut pids: Vec<u32> = top_level_windows()
+        .into_iter()
+        .map(|hwnd| {
+            let mut pid = 0u32;
+            unsafe {
+                GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            }
+            pid
+        })
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    pids.into_iter().filter_map(process_name).collect()
+}
+
+/// The center of the monitor holding the foreground window, in physical desktop pixels
+pub fn active_monitor_point() -> Option<(f64, f64)> {
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONULL,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+        if monitor.is_invalid() {
+            return None;
+        }
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return None;
+        }
+        let rect = info.rcMonitor;
+        Some((
+            (rect.left + rect.right) as f64 / 2.0,
+            (rect.top + rect.bottom) as f64 / 2.0,
+        ))
+    }
+}
+
+/// GDI capture needs no permission
+pub fn can_capture() -> bool {
+    true
 }
@@ -0,0 +1,45 @@
+use super::TRAY_ID;
+use tauri::{AppHandle, Manager, UserAttentionType};
+
+/// Draw the user's eye to the overlay (taskbar flash on Windows, Dock bounce on macOS)
+///
+/// As an accessory app Lens has no Dock icon on macOS, so the tray icon is marked too.
+#[tauri::command]
+pub fn request_attention(app: AppHandle, critical: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+
+    let level = if critical {
+        UserAttentionType::Critical
+    } else {
+        UserAttentionType::Informational
+    };
+    window
+        .request_user_attention(Some(level))
+        .map_err(|e| e.to_string())?;
+
+    #[cfg(target_os = "macos")]
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let marker = if critical { "●" } else { "•" };
+        let _ = tray.set_title(Some(marker));
+    }
+
+    Ok(())
+}
+
+/// Cancel a pending attention request
+#[tauri::command]
+pub fn cancel_attention(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .request_user_attention(None)
+            .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_title(None::<&str>);
+    }
+
+    Ok(())
+}
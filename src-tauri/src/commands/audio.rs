@@ -0,0 +1,82 @@
+use crate::config;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Whether app sounds are muted; backend sound paths should check this before playing
+pub fn is_muted(app: &AppHandle) -> bool {
+    config::load_config(app).muted
+}
+
+/// Get the app-wide mute switch
+#[tauri::command]
+pub fn get_muted(app: AppHandle) -> bool {
+    is_muted(&app)
+}
+
+/// Mute or unmute all app sounds, and tell the frontend so it can mute its own audio
+#[tauri::command]
+pub fn set_muted(app: AppHandle, muted: bool) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.muted = muted;
+    config::save_config(&app, &config, "set_muted")?;
+
+    app.emit("mute-changed", muted).map_err(|e| e.to_string())
+}
+
+/// An audio endpoint; `id` is stable for as long as the device name is
+#[derive(Debug, Serialize, Clone)]
+pub struct AudioDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub is_input: bool,
+}
+
+/// List audio input and output devices on the default host
+///
+/// Devices whose name can't be read are skipped; only a failure to enumerate either kind
+/// of device is an error.
+#[tauri::command]
+pub fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    #[cfg(feature = "audio")]
+    {
+        use cpal::traits::{DeviceTrait, HostTrait};
+
+        let host = cpal::default_host();
+        let default_input = host.default_input_device().and_then(|d| d.name().ok());
+        let default_output = host.default_output_device().and_then(|d| d.name().ok());
+
+        let describe = |device: cpal::Device, is_input: bool| {
+            let name = device.name().ok()?;
+            let default = if is_input {
+                &default_input
+            } else {
+                &default_output
+            };
+            Some(AudioDevice {
+                id: format!("{}:{}", if is_input { "input" } else { "output" }, name),
+                is_default: default.as_ref() == Some(&name),
+                name,
+                is_input,
+            })
+        };
+
+        let outputs = host.output_devices();
+        let inputs = host.input_devices();
+        if let (Err(output_error), Err(_)) = (&outputs, &inputs) {
+            return Err(output_error.to_string());
+        }
+
+        let mut devices: Vec<AudioDevice> = Vec::new();
+        if let Ok(outputs) = outputs {
+            devices.extend(outputs.filter_map(|d| describe(d, false)));
+        }
+        if let Ok(inputs) = inputs {
+            devices.extend(inputs.filter_map(|d| describe(d, true)));
+        }
+        Ok(devices)
+    }
+
+    #[cfg(not(feature = "audio"))]
+    Err("Audio device support is not available in this build".to_string())
+}
@@ -0,0 +1,178 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+/// The app that was frontmost before focus_for_input took focus (pid on macOS, HWND on Windows)
+#[derive(Default)]
+pub struct InputFocusState(Mutex<Option<isize>>);
+
+/// Make the overlay the key window so typing reaches the ask box
+///
+/// As an accessory app on macOS the overlay doesn't always become key on its own, and
+/// keystrokes go to the app behind it. This activates the app just for now, without
+/// changing the activation policy, and remembers who had focus for release_input_focus.
+#[tauri::command]
+pub fn focus_for_input(app: AppHandle, state: State<'_, InputFocusState>) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+
+    if let Some(previous) = frontmost_app() {
+        *state.0.lock().unwrap() = Some(previous);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSApplication, NSWindow};
+        use cocoa::base::{id, nil, YES};
+
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        unsafe {
+            NSApplication::sharedApplication(nil).activateIgnoringOtherApps_(YES);
+            ns_window.makeKeyAndOrderFront_(nil);
+        }
+    }
+
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Hand focus back to the app that had it before focus_for_input
+#[tauri::command]
+pub fn release_input_focus(state: State<'_, InputFocusState>) -> Result<(), String> {
+    let Some(previous) = state.0.lock().unwrap().take() else {
+        return Ok(());
+    };
+    activate_app(previous)
+}
+
+/// The app that was frontmost when the overlay was last shown by toggle_window
+#[derive(Default)]
+pub struct ShowFocusState(Mutex<Option<isize>>);
+
+/// Remember the frontmost app so hiding the overlay can return focus to it
+pub(crate) fn remember_frontmost_app(app: &AppHandle) {
+    *app.state::<ShowFocusState>().0.lock().unwrap() = frontmost_app();
+}
+
+/// Return focus to the app remembered when the overlay was shown
+///
+/// The target is dropped either way, and silently skipped if that app has since closed.
+pub(crate) fn restore_frontmost_app(app: &AppHandle) {
+    let Some(previous) = app.state::<ShowFocusState>().0.lock().unwrap().take() else {
+        return;
+    };
+    if !app_is_alive(previous) {
+        return;
+    }
+    if let Err(e) = activate_app(previous) {
+        eprintln!("Failed to restore focus: {}", e);
+    }
+}
+
+/// Bring the app identified by frontmost_app back to the front
+fn activate_app(previous: isize) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        // NSApplicationActivateIgnoringOtherApps
+        const ACTIVATE_IGNORING_OTHER_APPS: u64 = 1 << 1;
+
+        unsafe {
+            let running: id = msg_send![class!(NSRunningApplication),
+                runningApplicationWithProcessIdentifier: previous as i32];
+            if running == nil {
+                return Err("Previous app is no longer running".to_string());
+            }
+            let _: bool = msg_send![running, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+
+        let restored = unsafe { SetForegroundWindow(HWND(previous as _)) };
+        if !restored.as_bool() {
+            return Err("Failed to restore the previous foreground window".to_string());
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = previous;
+
+    Ok(())
+}
+
+/// Whether the app identified by frontmost_app is still around
+fn app_is_alive(previous: isize) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let running: id = msg_send![class!(NSRunningApplication),
+                runningApplicationWithProcessIdentifier: previous as i32];
+            if running == nil {
+                return false;
+            }
+            let terminated: bool = msg_send![running, isTerminated];
+            !terminated
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::IsWindow;
+
+        unsafe { IsWindow(HWND(previous as _)).as_bool() }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = previous;
+        false
+    }
+}
+
+/// The currently frontmost app, unless it is Lens itself
+fn frontmost_app() -> Option<isize> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let frontmost: id = msg_send![workspace, frontmostApplication];
+            if frontmost == nil {
+                return None;
+            }
+            let pid: i32 = msg_send![frontmost, processIdentifier];
+            (pid as u32 != std::process::id()).then_some(pid as isize)
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetForegroundWindow, GetWindowThreadProcessId,
+        };
+
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_invalid() {
+                return None;
+            }
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            (pid != std::process::id()).then_some(hwnd.0 as isize)
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    None
+}
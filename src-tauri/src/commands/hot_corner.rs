@@ -0,0 +1,178 @@
+use crate::config::{self, HotCorner};
+use crate::monitors::{self, MonitorInfo};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the cursor is sampled while a hot corner is configured
+const HOT_CORNER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Distance from the screen edge, in physical pixels, that still counts as touching it
+const EDGE_MARGIN: f64 = 2.0;
+/// Size of the square, in physical pixels, that counts as a corner
+const CORNER_SIZE: f64 = 8.0;
+
+/// Tracks the running hot corner poller
+///
+/// Each start bumps the generation; a poller exits as soon as it sees a newer one, so
+/// restarting after a settings change cancels the previous poller.
+#[derive(Default)]
+pub struct HotCornerState {
+    generation: AtomicU64,
+}
+
+/// Set the hot corner and how long the cursor must rest there, or None to disable it
+#[tauri::command]
+pub fn set_hot_corner(
+    app: AppHandle,
+    corner: Option<HotCorner>,
+    dwell_ms: u64,
+) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.hot_corner.corner = corner;
+    config.hot_corner.dwell_ms = dwell_ms;
+    config::save_config(&app, &config, "set_hot_corner")?;
+    start_hot_corner_watcher(&app);
+    Ok(())
+}
+
+/// Start (or restart) the poller that shows the overlay when the cursor rests in the
+/// configured hot corner
+///
+/// Emits `hot-corner-triggered` with the corner and shows the window if it's hidden. It
+/// fires once per visit; the cursor has to leave the corner before it can fire again.
+/// Nothing happens while Do Not Disturb is on.
+pub fn start_hot_corner_watcher(app: &AppHandle) {
+    let generation = app
+        .state::<HotCornerState>()
+        .generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    let settings = config::load_config(app).hot_corner;
+    let Some(corner) = settings.corner else {
+        return;
+    };
+    let dwell = Duration::from_millis(settings.dwell_ms);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut entered_at: Option<Instant> = None;
+        let mut fired = false;
+        loop {
+            std::thread::sleep(HOT_CORNER_POLL_INTERVAL);
+            if app
+                .state::<HotCornerState>()
+                .generation
+                .load(Ordering::SeqCst)
+                != generation
+            {
+                break;
+            }
+
+            if !cursor_in_corner(&app, corner) {
+                entered_at = None;
+                fired = false;
+                continue;
+            }
+            let since = *entered_at.get_or_insert_with(Instant::now);
+            if fired || since.elapsed() < dwell {
+                continue;
+            }
+            fired = true;
+            if do_not_disturb_active() {
+                continue;
+            }
+
+            let _ = app.emit("hot-corner-triggered", corner);
+            let hidden = app
+                .get_webview_window("main")
+                .is_some_and(|window| !window.is_visible().unwrap_or(false));
+            if hidden {
+                super::toggle_window(app.clone());
+            }
+        }
+    });
+}
+
+/// Stop the hot corner poller until start_hot_corner_watcher is called again
+pub fn stop_hot_corner_watcher(app: &AppHandle) {
+    app.state::<HotCornerState>()
+        .generation
+        .fetch_add(1, Ordering::SeqCst);
+}
+
+fn cursor_in_corner(app: &AppHandle, corner: HotCorner) -> bool {
+    let Ok(position) = app.cursor_position() else {
+        return false;
+    };
+    monitors::list_monitors(app)
+        .iter()
+        .find(|m| contains(m, position.x, position.y))
+        .is_some_and(|m| hits(m, corner, position.x, position.y))
+}
+
+fn contains(monitor: &MonitorInfo, x: f64, y: f64) -> bool {
+    let (left, top) = (monitor.x as f64, monitor.y as f64);
+    x >= left && x < left + monitor.width as f64 && y >= top && y < top + monitor.height as f64
+}
+
+fn hits(monitor: &MonitorInfo, corner: HotCorner, x: f64, y: f64) -> bool {
+    let left = x - monitor.x as f64;
+    let top = y - monitor.y as f64;
+    let right = monitor.width as f64 - 1.0 - left;
+    let bottom = monitor.height as f64 - 1.0 - top;
+    let in_corner = |dx: f64, dy: f64| {
+        (dx <= EDGE_MARGIN && dy <= CORNER_SIZE) || (dy <= EDGE_MARGIN && dx <= CORNER_SIZE)
+    };
+
+    match corner {
+        HotCorner::TopLeft => in_corner(left, top),
+        HotCorner::TopRight => in_corner(right, top),
+        HotCorner::BottomLeft => in_corner(left, bottom),
+        HotCorner::BottomRight => in_corner(right, bottom),
+        HotCorner::TopEdge => top <= EDGE_MARGIN,
+        HotCorner::BottomEdge => bottom <= EDGE_MARGIN,
+        HotCorner::LeftEdge => left <= EDGE_MARGIN,
+        HotCorner::RightEdge => right <= EDGE_MARGIN,
+    }
+}
+
+/// Whether the OS is suppressing interruptions (Focus / Do Not Disturb, quiet hours,
+/// presentation mode)
+///
+/// Best effort: on macOS this reads the Focus assertion store, which may be unreadable
+/// without Full Disk Access, in which case DND is treated as off.
+#[cfg(target_os = "macos")]
+fn do_not_disturb_active() -> bool {
+    let Some(home) = std::env::var_os("HOME") else {
+        return false;
+    };
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let Ok(store) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return false;
+    };
+    store["data"].as_array().into_iter().flatten().any(|entry| {
+        entry["storeAssertionRecords"]
+            .as_array()
+            .is_some_and(|records| !records.is_empty())
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn do_not_disturb_active() -> bool {
+    use windows::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+    };
+
+    matches!(
+        unsafe { SHQueryUserNotificationState() },
+        Ok(state) if state == QUNS_QUIET_TIME || state == QUNS_PRESENTATION_MODE
+    )
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn do_not_disturb_active() -> bool {
+    false
+}
@@ -0,0 +1,177 @@
+use super::shortcuts::unregister_optional_shortcuts;
+use crate::config;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State, WindowEvent};
+
+/// How often the idle watcher checks whether the overlay should be hidden
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tracks user activity for the idle auto-hide timer
+pub struct IdleState {
+    last_activity: Mutex<Instant>,
+    /// Idle time before hiding, in milliseconds; 0 disables auto-hide
+    timeout_ms: AtomicU64,
+    /// Bumped on every blur, refocus, show and hide, so only the latest blur can hide
+    blur_generation: AtomicU64,
+}
+
+impl Default for IdleState {
+    fn default() -> Self {
+        Self {
+            last_activity: Mutex::new(Instant::now()),
+            timeout_ms: AtomicU64::new(0),
+            blur_generation: AtomicU64::new(0),
+        }
+    }
+}
+
+impl IdleState {
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    fn set_timeout(&self, auto_hide_after_ms: Option<u64>) {
+        self.timeout_ms
+            .store(auto_hide_after_ms.unwrap_or(0), Ordering::Relaxed);
+        self.touch();
+    }
+}
+
+/// Note user activity (focus, capture, shortcut) so the overlay isn't auto-hidden
+pub fn record_activity(app: &AppHandle) {
+    if let Some(state) = app.try_state::<IdleState>() {
+        state.touch();
+    }
+}
+
+/// Restart the idle countdown; the frontend calls this on user activity in the overlay
+#[tauri::command]
+pub fn reset_idle_timer(state: State<'_, IdleState>) {
+    state.touch();
+}
+
+/// Set how long the overlay may sit idle before hiding itself, or None to never auto-hide
+#[tauri::command]
+pub fn set_auto_hide_after(
+    app: AppHandle,
+    state: State<'_, IdleState>,
+    auto_hide_after_ms: Option<u64>,
+) -> Result<(), String> {
+    state.set_timeout(auto_hide_after_ms);
+
+    let mut config = config::load_config(&app);
+    config.auto_hide_after_ms = auto_hide_after_ms;
+    config::save_config(&app, &config, "set_auto_hide_after")
+}
+
+/// Turn auto-hide off until resume_idle_watcher, without touching the saved setting
+pub fn pause_idle_watcher(app: &AppHandle) {
+    app.state::<IdleState>().set_timeout(None);
+}
+
+/// Turn auto-hide back on with the saved setting
+pub fn resume_idle_watcher(app: &AppHandle) {
+    app.state::<IdleState>()
+        .set_timeout(config::load_config(app).auto_hide_after_ms);
+}
+
+/// Set how long the overlay may stay unfocused before hiding, or None to keep it up
+///
+/// The delay is a grace period: focus that comes back before it runs out (e.g. after a
+/// transient system panel) cancels the hide.
+#[tauri::command]
+pub fn set_hide_on_blur(app: AppHandle, delay_ms: Option<u64>) -> Result<(), String> {
+    cancel_blur_hide(&app);
+    let mut config = config::load_config(&app);
+    config.hide_on_blur_delay_ms = delay_ms;
+    config::save_config(&app, &config, "set_hide_on_blur")
+}
+
+/// Drop a pending blur hide; called when the window is shown or hidden explicitly
+pub fn cancel_blur_hide(app: &AppHandle) {
+    if let Some(state) = app.try_state::<IdleState>() {
+        state.blur_generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Hide the overlay after the blur grace period, unless something cancelled it meanwhile
+fn schedule_blur_hide(app: &AppHandle) {
+    let Some(delay_ms) = config::load_config(app).hide_on_blur_delay_ms else {
+        return;
+    };
+    let generation = app
+        .state::<IdleState>()
+        .blur_generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        if app
+            .state::<IdleState>()
+            .blur_generation
+            .load(Ordering::SeqCst)
+            != generation
+        {
+            return;
+        }
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        if window.is_visible().unwrap_or(false) && !window.is_focused().unwrap_or(false) {
+            let _ = window.hide();
+            unregister_optional_shortcuts(&app);
+            let _ = app.emit("window-hidden", ());
+        }
+    });
+}
+
+/// Start the background timer that hides the overlay after `auto_hide_after_ms` of inactivity
+///
+/// The overlay is only hidden while it's visible and unfocused, the same way toggle_window
+/// hides it.
+pub fn start_idle_watcher(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let state = app.state::<IdleState>();
+    state.set_timeout(config::load_config(app).auto_hide_after_ms);
+
+    let handle = app.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::Focused(true) => {
+            record_activity(&handle);
+            cancel_blur_hide(&handle);
+        }
+        WindowEvent::Focused(false) => schedule_blur_hide(&handle),
+        _ => {}
+    });
+
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+
+        let state = app.state::<IdleState>();
+        let timeout = state.timeout_ms.load(Ordering::Relaxed);
+        if timeout == 0 {
+            continue;
+        }
+        let idle = state.last_activity.lock().unwrap().elapsed();
+        if idle < Duration::from_millis(timeout) {
+            continue;
+        }
+
+        let visible = window.is_visible().unwrap_or(false);
+        let focused = window.is_focused().unwrap_or(false);
+        if visible && !focused {
+            let _ = window.hide();
+            unregister_optional_shortcuts(&app);
+            let _ = app.emit("window-hidden", ());
+        }
+        state.touch();
+    });
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Longest text type_text will synthesize keystrokes for; paste_text handles bigger payloads
+const MAX_TYPE_CHARS: usize = 4_000;
+/// Longest text paste_text will put on the clipboard
+const MAX_PASTE_BYTES: usize = 1024 * 1024;
+/// Time for focus to land on the previous app after the overlay hides
+const FOCUS_SETTLE: Duration = Duration::from_millis(150);
+/// Time for the target app to read the clipboard before the old contents are put back
+const PASTE_SETTLE: Duration = Duration::from_millis(300);
+
+/// Hide the overlay if it's showing, handing focus back to the app the user came from
+fn focus_previous_app(app: &AppHandle) {
+    let visible = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    if visible {
+        super::toggle_window(app.clone());
+        std::thread::sleep(FOCUS_SETTLE);
+    }
+}
+
+/// Type text into the app that had focus before Lens, as synthesized keystrokes
+///
+/// Hides the overlay first. Needs the Accessibility permission on macOS. Slow for long
+/// text, so it's capped; use paste_text for long answers.
+#[tauri::command]
+pub async fn type_text(app: AppHandle, text: String) -> Result<(), String> {
+    if text.is_empty() {
+        return Err("Nothing to type".to_string());
+    }
+    if text.chars().count() > MAX_TYPE_CHARS {
+        return Err(format!(
+            "Text is too long to type ({} characters max); paste it instead",
+            MAX_TYPE_CHARS
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        focus_previous_app(&app);
+        send_text(&text)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Paste text into the app that had focus before Lens via the clipboard
+///
+/// Hides the overlay first, then sets the clipboard, sends Cmd/Ctrl+V and puts the previous
+/// clipboard text or image back. Needs the Accessibility permission on macOS.
+#[tauri::command]
+pub async fn paste_text(app: AppHandle, text: String) -> Result<(), String> {
+    if text.is_empty() {
+        return Err("Nothing to paste".to_string());
+    }
+    if text.len() > MAX_PASTE_BYTES {
+        return Err(format!(
+            "Text is too large to paste ({} bytes max)",
+            MAX_PASTE_BYTES
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+        let previous_text = clipboard.get_text().ok();
+        let previous_image = previous_text
+            .is_none()
+            .then(|| clipboard.get_image().ok())
+            .flatten();
+
+        focus_previous_app(&app);
+        clipboard.set_text(text).map_err(|e| e.to_string())?;
+        let result = send_paste();
+        std::thread::sleep(PASTE_SETTLE);
+
+        if let Some(previous) = previous_text {
+            let _ = clipboard.set_text(previous);
+        } else if let Some(previous) = previous_image {
+            let _ = clipboard.set_image(previous);
+        } else {
+            let _ = clipboard.clear();
+        }
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(target_os = "macos")]
+fn send_text(text: &str) -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    // CGEventKeyboardSetUnicodeString takes at most 20 UTF-16 units per event
+    const MAX_UNITS_PER_EVENT: usize = 20;
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "Failed to create event source")?;
+    let units: Vec<u16> = text.encode_utf16().collect();
+    for chunk in units.chunks(MAX_UNITS_PER_EVENT) {
+        for key_down in [true, false] {
+            let event = CGEvent::new_keyboard_event(source.clone(), 0, key_down)
+                .map_err(|_| "Failed to create keyboard event")?;
+            event.set_string_from_utf16_unchecked(chunk);
+            event.post(CGEventTapLocation::HID);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn send_paste() -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    // kVK_ANSI_V
+    const KEY_V: u16 = 9;
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "Failed to create event source")?;
+    for key_down in [true, false] {
+        let event = CGEvent::new_keyboard_event(source.clone(), KEY_V, key_down)
+            .map_err(|_| "Failed to create keyboard event")?;
+        event.set_flags(CGEventFlags::CGEventFlagCommand);
+        event.post(CGEventTapLocation::HID);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn send_inputs(
+    inputs: &[windows::Win32::UI::Input::KeyboardAndMouse::INPUT],
+) -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT};
+
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err("Input was blocked by another application".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn key_input(
+    vk: windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY,
+    scan: u16,
+    flags: windows::Win32::UI::Input::KeyboardAndMouse::KEYBD_EVENT_FLAGS,
+) -> windows::Win32::UI::Input::KeyboardAndMouse::INPUT {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT};
+
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn send_text(text: &str) -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY,
+    };
+
+    let inputs: Vec<_> = text
+        .encode_utf16()
+        .flat_map(|unit| {
+            [
+                key_input(VIRTUAL_KEY(0), unit, KEYEVENTF_UNICODE),
+                key_input(VIRTUAL_KEY(0), unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+            ]
+        })
+        .collect();
+    send_inputs(&inputs)
+}
+
+#[cfg(target_os = "windows")]
+fn send_paste() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_CONTROL,
+    };
+
+    const VK_V: VIRTUAL_KEY = VIRTUAL_KEY(0x56);
+
+    send_inputs(&[
+        key_input(VK_CONTROL, 0, KEYBD_EVENT_FLAGS(0)),
+        key_input(VK_V, 0, KEYBD_EVENT_FLAGS(0)),
+        key_input(VK_V, 0, KEYEVENTF_KEYUP),
+        key_input(VK_CONTROL, 0, KEYEVENTF_KEYUP),
+    ])
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn send_text(_text: &str) -> Result<(), String> {
+    Err("Typing into other apps is not supported on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn send_paste() -> Result<(), String> {
+    Err("Pasting into other apps is not supported on this platform".to_string())
+}
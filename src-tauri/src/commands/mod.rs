@@ -1,4 +1,25 @@
+pub mod attention;
+pub mod audio;
+pub mod focus;
+pub mod hot_corner;
+pub mod idle;
+pub mod input;
+pub mod notifications;
+pub mod peek;
+pub mod permissions;
+pub mod profiles;
+pub mod quick_note;
+pub mod region;
 pub mod shortcuts;
+pub mod storage;
+pub mod tray;
+pub mod updates;
 pub mod window;
 
-pub use window::{toggle_window, resize_window, set_content_protection};
+pub use quick_note::toggle_quick_note;
+
+/// Id of the system tray icon created in setup()
+pub const TRAY_ID: &str = "main";
+pub use window::{
+    apply_window_settings, resize_window, set_content_protection, set_overlay_level, toggle_window,
+};
@@ -0,0 +1,43 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+
+/// Outcome of a test notification, so settings can tell the user what to fix
+#[derive(Debug, Serialize, Clone)]
+pub struct NotificationTest {
+    pub granted: bool,
+    pub permission: PermissionState,
+}
+
+/// Show a sample notification, asking for permission first if it hasn't been decided
+///
+/// Nothing is shown when permission is denied; the returned state says so instead.
+#[tauri::command]
+pub fn send_test_notification(app: AppHandle) -> Result<NotificationTest, String> {
+    let notification = app.notification();
+
+    let mut permission = notification.permission_state().map_err(|e| e.to_string())?;
+    if matches!(
+        permission,
+        PermissionState::Prompt | PermissionState::PromptWithRationale
+    ) {
+        permission = notification
+            .request_permission()
+            .map_err(|e| e.to_string())?;
+    }
+
+    let granted = permission == PermissionState::Granted;
+    if granted {
+        notification
+            .builder()
+            .title("Lens")
+            .body("Notifications are working.")
+            .show()
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(NotificationTest {
+        granted,
+        permission,
+    })
+}
@@ -0,0 +1,104 @@
+use crate::config;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Longest a peek lasts without a release event, in case the OS never delivers it
+const PEEK_RELEASE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tracks a peek in progress: the overlay shown while the peek shortcut is held
+#[derive(Default)]
+pub struct PeekState {
+    active: AtomicBool,
+    /// Whether the peek showed the window, so release only hides what it showed
+    shown_by_peek: AtomicBool,
+    /// Bumped on every peek so a stale release watchdog can't end a newer one
+    generation: AtomicU64,
+}
+
+fn peek_shortcut(app: &AppHandle) -> Option<Shortcut> {
+    config::load_config(app)
+        .peek_shortcut
+        .and_then(|combo| combo.parse().ok())
+}
+
+/// Handle a global shortcut event for the peek binding; returns true if it was consumed
+///
+/// Unlike the other actions this reacts to both Pressed and Released.
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, state: ShortcutState) -> bool {
+    if peek_shortcut(app).map(|s| s.id()) != Some(shortcut.id()) {
+        return false;
+    }
+    match state {
+        ShortcutState::Pressed => start_peek(app),
+        ShortcutState::Released => end_peek(app),
+    }
+    true
+}
+
+fn start_peek(app: &AppHandle) {
+    let state = app.state::<PeekState>();
+    // Key repeat keeps sending presses while the shortcut is held
+    if state.active.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    if let Some(window) = app.get_webview_window("main") {
+        let visible = window.is_visible().unwrap_or(false);
+        state.shown_by_peek.store(!visible, Ordering::SeqCst);
+        if !visible {
+            let _ = window.show();
+        }
+    }
+    let _ = app.emit("peek-start", ());
+
+    // If the release is lost (e.g. focus moved to a secure input field), end the peek anyway
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(PEEK_RELEASE_TIMEOUT);
+        if app.state::<PeekState>().generation.load(Ordering::SeqCst) == generation {
+            end_peek(&app);
+        }
+    });
+}
+
+fn end_peek(app: &AppHandle) {
+    let state = app.state::<PeekState>();
+    if !state.active.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if state.shown_by_peek.swap(false, Ordering::SeqCst) {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+    let _ = app.emit("peek-end", ());
+}
+
+/// Set or clear the hold-to-peek shortcut, e.g. "CommandOrControl+Alt+Space"
+///
+/// Holding it shows the overlay (emitting `peek-start`) and releasing hides it again
+/// (emitting `peek-end`). It stays registered while the window is hidden.
+#[tauri::command]
+pub fn set_peek_shortcut(app: AppHandle, shortcut: Option<String>) -> Result<(), String> {
+    let parsed = shortcut
+        .as_deref()
+        .map(|combo| combo.parse::<Shortcut>().map_err(|e| e.to_string()))
+        .transpose()?;
+
+    if let Some(old) = peek_shortcut(&app) {
+        let _ = app.global_shortcut().unregister(old);
+    }
+    end_peek(&app);
+    if let Some(new) = parsed {
+        app.global_shortcut()
+            .register(new)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut config = config::load_config(&app);
+    config.peek_shortcut = shortcut;
+    config::save_config(&app, &config, "set_peek_shortcut")
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+use tauri_plugin_opener::OpenerExt;
+
+/// Whether an OS permission Lens relies on has been granted
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    /// The user hasn't been asked yet
+    NotDetermined,
+    /// The platform has no such permission
+    NotApplicable,
+}
+
+/// The OS permissions Lens uses
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub enum Permission {
+    /// Screen capture (macOS Screen Recording)
+    ScreenRecording,
+    /// Synthesized input for type_text/paste_text (macOS Accessibility)
+    Accessibility,
+    Notifications,
+}
+
+/// Status of every permission Lens uses, for a single settings page
+#[derive(Debug, Serialize, Clone)]
+pub struct Permissions {
+    pub screen_recording: PermissionStatus,
+    pub accessibility: PermissionStatus,
+    pub notifications: PermissionStatus,
+}
+
+/// Current status of the OS permissions Lens relies on
+///
+/// macOS can't tell an undecided Screen Recording or Accessibility permission from a
+/// denied one without prompting, so both report Denied until granted.
+#[tauri::command]
+pub fn get_permissions(app: AppHandle) -> Permissions {
+    let notifications = match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => PermissionStatus::Granted,
+        Ok(PermissionState::Denied) => PermissionStatus::Denied,
+        Ok(_) => PermissionStatus::NotDetermined,
+        Err(_) => PermissionStatus::NotApplicable,
+    };
+
+    Permissions {
+        screen_recording: screen_recording_status(),
+        accessibility: accessibility_status(),
+        notifications,
+    }
+}
+
+/// Open the system settings page where `permission` is granted or revoked
+#[tauri::command]
+pub fn open_permission_settings(app: AppHandle, permission: Permission) -> Result<(), String> {
+    let url = settings_url(permission)
+        .ok_or_else(|| format!("{:?} has no settings page on this platform", permission))?;
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+}
+
+#[cfg(target_os = "macos")]
+fn granted_or_denied(granted: bool) -> PermissionStatus {
+    if granted {
+        PermissionStatus::Granted
+    } else {
+        PermissionStatus::Denied
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn screen_recording_status() -> PermissionStatus {
+    granted_or_denied(unsafe { CGPreflightScreenCaptureAccess() })
+}
+
+#[cfg(target_os = "macos")]
+fn accessibility_status() -> PermissionStatus {
+    granted_or_denied(unsafe { AXIsProcessTrusted() })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn screen_recording_status() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+#[cfg(not(target_os = "macos"))]
+fn accessibility_status() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+#[cfg(target_os = "macos")]
+fn settings_url(permission: Permission) -> Option<&'static str> {
+    Some(match permission {
+        Permission::ScreenRecording => {
+            "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+        }
+        Permission::Accessibility => {
+            "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+        }
+        Permission::Notifications => "x-apple.systempreferences:com.apple.preference.notifications",
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn settings_url(permission: Permission) -> Option<&'static str> {
+    match permission {
+        Permission::Notifications => Some("ms-settings:notifications"),
+        Permission::ScreenRecording | Permission::Accessibility => None,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn settings_url(_permission: Permission) -> Option<&'static str> {
+    None
+}
@@ -0,0 +1,80 @@
+use super::shortcuts::replace_shortcuts;
+use crate::config::{self, ShortcutProfile};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Vec<ShortcutProfile> {
+    config::load_config(&app).profiles
+}
+
+/// Save the current shortcuts as a named profile, overwriting a profile with the same name
+#[tauri::command]
+pub fn save_profile(app: AppHandle, name: String) -> Result<ShortcutProfile, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    let mut config = config::load_config(&app);
+    let shortcuts = config.shortcuts.clone();
+
+    let profile = if let Some(existing) = config.profiles.iter_mut().find(|p| p.name == name) {
+        existing.shortcuts = shortcuts;
+        existing.clone()
+    } else {
+        let id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_millis()
+            .to_string();
+        let profile = ShortcutProfile {
+            id,
+            name,
+            shortcuts,
+        };
+        config.profiles.push(profile.clone());
+        profile
+    };
+
+    config::save_config(&app, &config, "save_profile")?;
+    Ok(profile)
+}
+
+/// Switch to another profile, swapping the registered shortcuts as one unit
+#[tauri::command]
+pub fn activate_profile(app: AppHandle, id: String) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    let profile = config
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or("Profile not found")?;
+
+    replace_shortcuts(&app, &mut config, profile.shortcuts)?;
+    config.active_profile = Some(profile.id);
+
+    config::save_config(&app, &config, "activate_profile")?;
+
+    // Notify frontend to update UI
+    let _ = app.emit("shortcuts-changed", ());
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, id: String) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    if config.active_profile.as_ref() == Some(&id) {
+        return Err("Cannot delete the active profile".to_string());
+    }
+
+    let before = config.profiles.len();
+    config.profiles.retain(|p| p.id != id);
+    if config.profiles.len() == before {
+        return Err("Profile not found".to_string());
+    }
+
+    config::save_config(&app, &config, "delete_profile")
+}
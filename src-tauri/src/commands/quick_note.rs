@@ -0,0 +1,106 @@
+use super::window::ContentProtectionState;
+use crate::config;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+pub const QUICK_NOTE_LABEL: &str = "quicknote";
+
+/// How long the note has to stay put after a move or resize before its geometry is saved
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// PendingSaveState key for the note's geometry
+const GEOMETRY_SAVE_KEY: &str = "quick_note";
+
+/// Tracks moves and resizes of the quick note so only the last event of a drag saves
+#[derive(Default)]
+pub struct QuickNoteState {
+    /// Bumped on every move or resize; a pending save only runs if it's still current
+    geometry_generation: AtomicU64,
+}
+
+/// Show or hide the quick note window, creating it on first use
+#[tauri::command]
+pub fn toggle_quick_note(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUICK_NOTE_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+            let _ = app.emit("quick-note-hidden", ());
+        } else {
+            let _ = window.show();
+            let _ = window.set_focus();
+            let _ = app.emit("quick-note-shown", ());
+        }
+        return Ok(());
+    }
+
+    let geometry = config::load_config(&app).quick_note;
+    let content_protected = app
+        .state::<ContentProtectionState>()
+        .is_enabled_for(QUICK_NOTE_LABEL);
+
+    let window = WebviewWindowBuilder::new(
+        &app,
+        QUICK_NOTE_LABEL,
+        WebviewUrl::App("quicknote.html".into()),
+    )
+    .title("Quick Note")
+    .inner_size(geometry.width, geometry.height)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .content_protected(content_protected)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    if let (Some(x), Some(y)) = (geometry.x, geometry.y) {
+        let _ = window.set_position(PhysicalPosition::new(x, y));
+    }
+
+    // Persist geometry separately from the main overlay once the note settles after a move
+    // or resize, rather than on each of the many events a drag sends
+    let handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Moved(_) | WindowEvent::Resized(_) = event {
+            defer_geometry_save(&handle, &tracked);
+            let generation = handle
+                .state::<QuickNoteState>()
+                .geometry_generation
+                .fetch_add(1, Ordering::SeqCst)
+                + 1;
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(GEOMETRY_SAVE_DELAY);
+                let state = handle.state::<QuickNoteState>();
+                if state.geometry_generation.load(Ordering::SeqCst) == generation {
+                    let _ = config::save_deferred(&handle, GEOMETRY_SAVE_KEY);
+                }
+            });
+        }
+    });
+
+    let _ = app.emit("quick-note-shown", ());
+    Ok(())
+}
+
+/// Queue the note's current position and logical size for the quick_note config
+fn defer_geometry_save(app: &AppHandle, window: &WebviewWindow) {
+    let (Ok(position), Ok(size), Ok(scale)) = (
+        window.outer_position(),
+        window.inner_size(),
+        window.scale_factor(),
+    ) else {
+        return;
+    };
+    let logical = size.to_logical::<f64>(scale);
+    config::defer_save(app, GEOMETRY_SAVE_KEY, move |config| {
+        config.quick_note.x = Some(position.x);
+        config.quick_note.y = Some(position.y);
+        config.quick_note.width = logical.width;
+        config.quick_note.height = logical.height;
+    });
+}
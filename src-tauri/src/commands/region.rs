@@ -0,0 +1,145 @@
+use crate::capture::{self, CaptureFormat, CaptureRect, CaptureResult, PngCompression};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, State, WebviewUrl,
+    WebviewWindowBuilder,
+};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut};
+
+/// Label prefix for the per-monitor selection windows
+pub const REGION_SELECT_LABEL_PREFIX: &str = "region-select-";
+
+/// Time for the selection windows to disappear before the screen is read
+const SELECTION_TEARDOWN_DELAY: Duration = Duration::from_millis(150);
+
+/// Set while region selection windows are up
+#[derive(Default)]
+pub struct RegionSelectionState(pub AtomicBool);
+
+fn escape_shortcut() -> Shortcut {
+    Shortcut::new(None, Code::Escape)
+}
+
+/// Cover every monitor with a transparent selection window the user can drag across
+///
+/// Each window loads regionselect.html, which draws the selection and calls
+/// complete_region_selection with the chosen rectangle in physical desktop coordinates.
+/// Escape cancels from anywhere.
+#[tauri::command]
+pub fn begin_region_selection(
+    app: AppHandle,
+    state: State<'_, RegionSelectionState>,
+) -> Result<(), String> {
+    if state.0.swap(true, Ordering::SeqCst) {
+        return Err("Region selection is already in progress".to_string());
+    }
+
+    let monitors = match app.available_monitors() {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        Ok(_) => {
+            state.0.store(false, Ordering::SeqCst);
+            return Err("No monitors found".to_string());
+        }
+        Err(e) => {
+            state.0.store(false, Ordering::SeqCst);
+            return Err(e.to_string());
+        }
+    };
+
+    for (index, monitor) in monitors.iter().enumerate() {
+        let label = format!("{}{}", REGION_SELECT_LABEL_PREFIX, index);
+        let built =
+            WebviewWindowBuilder::new(&app, &label, WebviewUrl::App("regionselect.html".into()))
+                .title("Select Region")
+                .decorations(false)
+                .transparent(true)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .resizable(false)
+                .shadow(false)
+                .visible(false)
+                .build();
+        let window = match built {
+            Ok(window) => window,
+            Err(e) => {
+                close_selection_windows(&app);
+                state.0.store(false, Ordering::SeqCst);
+                return Err(e.to_string());
+            }
+        };
+
+        let position = monitor.position();
+        let size = monitor.size();
+        let _ = window.set_position(PhysicalPosition::new(position.x, position.y));
+        let _ = window.set_size(PhysicalSize::new(size.width, size.height));
+        let _ = window.show();
+        if index == 0 {
+            let _ = window.set_focus();
+        }
+    }
+
+    if let Err(e) = app.global_shortcut().register(escape_shortcut()) {
+        eprintln!("Failed to register Escape for region selection: {}", e);
+    }
+    Ok(())
+}
+
+/// Close the selection windows and capture the chosen rectangle
+///
+/// The capture is also emitted as `capture-ready`, since the selection window that called
+/// this is gone by the time it returns.
+#[tauri::command]
+pub async fn complete_region_selection(
+    app: AppHandle,
+    state: State<'_, RegionSelectionState>,
+    rect: CaptureRect,
+    compression: Option<PngCompression>,
+    format: Option<CaptureFormat>,
+) -> Result<CaptureResult, String> {
+    if !state.0.swap(false, Ordering::SeqCst) {
+        return Err("No region selection in progress".to_string());
+    }
+    end_selection(&app);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // Let the selection windows vanish so they aren't in the capture
+        std::thread::sleep(SELECTION_TEARDOWN_DELAY);
+        let result = capture::capture_region(app.clone(), rect, compression, format)?;
+        let _ = app.emit("capture-ready", &result);
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Abandon region selection and emit `region-selection-cancelled`
+#[tauri::command]
+pub fn cancel_region_selection(app: AppHandle, state: State<'_, RegionSelectionState>) {
+    if state.0.swap(false, Ordering::SeqCst) {
+        end_selection(&app);
+        let _ = app.emit("region-selection-cancelled", ());
+    }
+}
+
+/// Handle a global shortcut press while selecting; returns true if it was consumed
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut) -> bool {
+    if shortcut.id() != escape_shortcut().id() {
+        return false;
+    }
+    cancel_region_selection(app.clone(), app.state::<RegionSelectionState>());
+    true
+}
+
+fn end_selection(app: &AppHandle) {
+    let _ = app.global_shortcut().unregister(escape_shortcut());
+    close_selection_windows(app);
+}
+
+fn close_selection_windows(app: &AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label.starts_with(REGION_SELECT_LABEL_PREFIX) {
+            let _ = window.destroy();
+        }
+    }
+}
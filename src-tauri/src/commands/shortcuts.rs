@@ -1,6 +1,16 @@
-use crate::config::{self, ShortcutsConfig};
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
+use super::quick_note::toggle_quick_note;
+use crate::capture::{capture_active_monitor, capture_and_emit};
+use crate::config::{self, AppConfig, CaptureTarget, ShortcutsConfig};
+use crate::last_errors::{self, Subsystem};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_global_shortcut::{
+    Code, GlobalShortcut, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState,
+};
 
 /// Get the optional shortcuts that are registered when window is visible
 #[allow(dead_code)]
@@ -11,20 +21,117 @@ pub fn get_optional_shortcuts() -> [Shortcut; 2] {
     ]
 }
 
+/// Whether `action`'s shortcut stays registered while the window is hidden
+pub fn always_registered(config: &AppConfig, action: &str) -> bool {
+    action == "toggle" || (action == "ask" && config.ask_shows_window)
+}
+
+/// Combos for the actions that are only registered while the window is visible
+fn optional_combos(config: &AppConfig) -> impl Iterator<Item = &String> {
+    config
+        .shortcuts
+        .bindings()
+        .into_iter()
+        .filter(|(action, _)| !always_registered(config, action))
+        .flat_map(|(_, combos)| combos)
+}
+
+/// The screen_share shortcuts with each configured extra modifier added, and their targets
+///
+/// Global hotkeys match an exact set of modifiers: with Shift held the plain screen_share
+/// hotkey never fires, and the OS doesn't report which other keys were down when one does.
+/// So rather than reading modifier state at trigger time, each variant is registered as a
+/// hotkey of its own.
+pub fn capture_variants(config: &AppConfig) -> Vec<(Shortcut, CaptureTarget)> {
+    config
+        .capture
+        .modifier_targets
+        .iter()
+        .flat_map(|variant| {
+            config
+                .shortcuts
+                .screen_share
+                .iter()
+                .filter_map(move |combo| {
+                    let base = combo.parse::<Shortcut>().ok()?;
+                    let shortcut = format!("{}+{}", variant.modifier, combo)
+                        .parse::<Shortcut>()
+                        .ok()?;
+                    (shortcut != base).then_some((shortcut, variant.target))
+                })
+        })
+        .collect()
+}
+
+/// What a pressed global shortcut is bound to
+#[derive(Debug, PartialEq)]
+pub enum ShortcutBinding {
+    /// One of the screen_share modifier variants, capturing its target
+    Capture(CaptureTarget),
+    Action(&'static str),
+}
+
+/// The action or capture variant `shortcut` is bound to in `config`
+///
+/// config.json is user-editable, so combos that don't parse are skipped instead of
+/// panicking inside the handler; log_invalid_combos reports them once at startup.
+pub fn binding_for(config: &AppConfig, shortcut: &Shortcut) -> Option<ShortcutBinding> {
+    if let Some((_, target)) = capture_variants(config)
+        .into_iter()
+        .find(|(variant, _)| variant == shortcut)
+    {
+        return Some(ShortcutBinding::Capture(target));
+    }
+    config
+        .shortcuts
+        .bindings()
+        .into_iter()
+        .find(|(_, combos)| {
+            combos
+                .iter()
+                .filter_map(|combo| combo.parse::<Shortcut>().ok())
+                .any(|parsed| &parsed == shortcut)
+        })
+        .map(|(action, _)| ShortcutBinding::Action(action))
+}
+
+/// Log each configured combo that doesn't parse, which binding_for then ignores
+pub fn log_invalid_combos(config: &AppConfig) {
+    for (action, combos) in config.shortcuts.bindings() {
+        for combo in combos {
+            if let Err(e) = combo.parse::<Shortcut>() {
+                eprintln!("Skipping invalid {} shortcut {}: {}", action, combo, e);
+            }
+        }
+    }
+}
+
 /// Register optional shortcuts (called when window becomes visible)
 pub fn register_optional_shortcuts(app: &AppHandle) {
-    // Note: This logic might need to be adjusted if we want these to be dynamic too.
-    // For now, let's keep the hardcoded logic for "optional" ones if they are strictly internal,
-    // BUT the requirement is to customize "Ask AI" and "Screen Share".
-    // So we should probably use the config values here too.
-
     let config = config::load_config(app);
 
-    if let Ok(shortcut) = config.shortcuts.ask.parse::<Shortcut>() {
-        let _ = app.global_shortcut().register(shortcut);
+    let mut errors = Vec::new();
+    for combo in optional_combos(&config) {
+        if let Ok(shortcut) = combo.parse::<Shortcut>() {
+            if let Err(e) = app.global_shortcut().register(shortcut) {
+                errors.push(format!("{}: {}", combo, e));
+            }
+        }
+    }
+    for (shortcut, _) in capture_variants(&config) {
+        if let Err(e) = app.global_shortcut().register(shortcut) {
+            errors.push(format!("{}: {}", shortcut, e));
+        }
     }
-    if let Ok(shortcut) = config.shortcuts.screen_share.parse::<Shortcut>() {
-        let _ = app.global_shortcut().register(shortcut);
+    track_registration_errors(app, errors);
+}
+
+/// Note failed registrations for get_last_errors, or clear the last one if all worked
+pub fn track_registration_errors(app: &AppHandle, errors: Vec<String>) {
+    if errors.is_empty() {
+        last_errors::clear_error(app, Subsystem::Shortcuts);
+    } else {
+        last_errors::record_error(app, Subsystem::Shortcuts, errors.join(", "));
     }
 }
 
@@ -32,73 +139,796 @@ pub fn register_optional_shortcuts(app: &AppHandle) {
 pub fn unregister_optional_shortcuts(app: &AppHandle) {
     let config = config::load_config(app);
 
-    if let Ok(shortcut) = config.shortcuts.ask.parse::<Shortcut>() {
-        let _ = app.global_shortcut().unregister(shortcut);
+    for combo in optional_combos(&config) {
+        if let Ok(shortcut) = combo.parse::<Shortcut>() {
+            let _ = app.global_shortcut().unregister(shortcut);
+        }
     }
-    if let Ok(shortcut) = config.shortcuts.screen_share.parse::<Shortcut>() {
+    for (shortcut, _) in capture_variants(&config) {
         let _ = app.global_shortcut().unregister(shortcut);
     }
 }
 
+/// Capture `target` for the screen_share action, emitting it in `screen-share-triggered`
+///
+/// Captures arrive as `capture-ready`; for a region capture that happens once the user has
+/// dragged out a rectangle in the selection windows.
+pub fn run_screen_share(app: &AppHandle, target: CaptureTarget) -> Result<(), String> {
+    super::idle::record_activity(app);
+    let _ = app.emit("screen-share-triggered", target);
+
+    if target == CaptureTarget::Region {
+        return super::region::begin_region_selection(app.clone(), app.state());
+    }
+
+    // Capture off the shortcut handler thread
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let result = match target {
+            CaptureTarget::ActiveMonitor => capture_active_monitor(app.clone(), None, None)
+                .and_then(|capture| {
+                    app.emit("capture-ready", capture)
+                        .map_err(|e| e.to_string())
+                }),
+            _ => capture_and_emit(app, None),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to capture screen: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Release every global shortcut Lens holds, so none outlive the process
+///
+/// Called on exit. The optional and toggle shortcuts are unregistered by name first; the
+/// final unregister_all catches anything else, such as the region selection Escape.
+pub fn teardown_shortcuts(app: &AppHandle) {
+    release_shortcuts(&config::load_config(app), app.global_shortcut());
+}
+
+/// The registry operations teardown and replace_shortcuts need, so they can run against a
+/// fake in tests
+trait ShortcutRegistry {
+    fn register(&self, shortcut: Shortcut) -> Result<(), String>;
+    fn unregister(&self, shortcut: Shortcut);
+    fn unregister_all(&self);
+}
+
+impl<R: Runtime> ShortcutRegistry for GlobalShortcut<R> {
+    fn register(&self, shortcut: Shortcut) -> Result<(), String> {
+        GlobalShortcut::register(self, shortcut).map_err(|e| e.to_string())
+    }
+
+    fn unregister(&self, shortcut: Shortcut) {
+        let _ = GlobalShortcut::unregister(self, shortcut);
+    }
+
+    fn unregister_all(&self) {
+        let _ = GlobalShortcut::unregister_all(self);
+    }
+}
+
+fn release_shortcuts(config: &AppConfig, registry: &impl ShortcutRegistry) {
+    let bound = optional_combos(config).chain(&config.shortcuts.toggle);
+    for shortcut in bound.filter_map(|combo| combo.parse::<Shortcut>().ok()) {
+        registry.unregister(shortcut);
+    }
+    for (shortcut, _) in capture_variants(config) {
+        registry.unregister(shortcut);
+    }
+    registry.unregister_all();
+}
+
+/// How long a try_register_shortcut registration lives if the UI never undoes it
+const TEST_SHORTCUT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest gap between two presses of a held shortcut that still counts as key repeat
+///
+/// A release can be lost, e.g. when the shortcut is unregistered while held; a press after
+/// this long is treated as a fresh one rather than ignored forever.
+const KEY_REPEAT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Shortcuts currently held down, by id, with when their last press arrived
+#[derive(Default)]
+struct KeyRepeatTracker(HashMap<u32, Instant>);
+
+impl KeyRepeatTracker {
+    /// Record a press or release of shortcut `id` at `now`; true if the press is a repeat
+    fn observe(&mut self, id: u32, state: ShortcutState, now: Instant) -> bool {
+        match state {
+            ShortcutState::Pressed => self
+                .0
+                .insert(id, now)
+                .is_some_and(|last| now.duration_since(last) < KEY_REPEAT_WINDOW),
+            ShortcutState::Released => {
+                self.0.remove(&id);
+                false
+            }
+        }
+    }
+}
+
+/// Key repeat tracking for every registered shortcut
+#[derive(Default)]
+pub struct HeldShortcutsState(Mutex<KeyRepeatTracker>);
+
+/// Track press and release of every shortcut; returns true for a key repeat to ignore
+///
+/// While a shortcut is held the OS keeps sending Pressed events, which would re-run its
+/// action (or a hold action's show) over and over. Only the first press after a release
+/// gets through, unless `allow_key_repeat` is set. Releases always get through.
+pub fn is_key_repeat(app: &AppHandle, shortcut: &Shortcut, state: ShortcutState) -> bool {
+    let held = app.state::<HeldShortcutsState>();
+    let repeat = held
+        .0
+        .lock()
+        .unwrap()
+        .observe(shortcut.id(), state, Instant::now());
+    repeat && !config::load_config(app).allow_key_repeat
+}
+
+/// When each shortcut action last ran successfully, by action name
+#[derive(Default)]
+struct CooldownTracker(HashMap<String, Instant>);
+
+impl CooldownTracker {
+    /// How much of `cooldown` is left at `now` since `action` last ran, if any
+    fn remaining(&self, action: &str, cooldown: Duration, now: Instant) -> Option<Duration> {
+        self.0
+            .get(action)
+            .and_then(|last| cooldown.checked_sub(now.duration_since(*last)))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    fn record(&mut self, action: &str, now: Instant) {
+        self.0.insert(action.to_string(), now);
+    }
+}
+
+/// Cooldown tracking for action_cooldowns_ms
+#[derive(Default)]
+pub struct ActionCooldownState(Mutex<CooldownTracker>);
+
+/// Payload of `action-on-cooldown`
+#[derive(Debug, Serialize, Clone)]
+pub struct ActionOnCooldown {
+    pub action: String,
+    pub remaining_ms: u64,
+}
+
+/// Run a shortcut action unless it's still cooling down from its last successful run
+///
+/// Unlike the key repeat guard this blocks deliberate presses too, for expensive actions
+/// like capture. A blocked press emits `action-on-cooldown` with the time left and counts
+/// as handled. A run that returns an error doesn't start the cooldown, but screen_share
+/// returns once its capture has started, so a capture that fails later still counts.
+pub fn run_with_cooldown(
+    app: &AppHandle,
+    action: &str,
+    run: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    let cooldown = config::load_config(app)
+        .action_cooldowns_ms
+        .get(action)
+        .copied()
+        .unwrap_or(0);
+    if cooldown == 0 {
+        return run();
+    }
+
+    let state = app.state::<ActionCooldownState>();
+    let remaining =
+        state
+            .0
+            .lock()
+            .unwrap()
+            .remaining(action, Duration::from_millis(cooldown), Instant::now());
+    if let Some(remaining) = remaining {
+        let _ = app.emit(
+            "action-on-cooldown",
+            ActionOnCooldown {
+                action: action.to_string(),
+                remaining_ms: remaining.as_millis() as u64,
+            },
+        );
+        return Ok(());
+    }
+
+    run()?;
+    state.0.lock().unwrap().record(action, Instant::now());
+    Ok(())
+}
+
+/// Set how long `action` is blocked after it runs from its shortcut; 0 turns it off
+#[tauri::command]
+pub fn set_action_cooldown(app: AppHandle, action: String, cooldown_ms: u64) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    if config.shortcuts.action_mut(&action).is_none() {
+        return Err(format!("Unknown action: {}", action));
+    }
+    if cooldown_ms == 0 {
+        config.action_cooldowns_ms.remove(&action);
+    } else {
+        config.action_cooldowns_ms.insert(action, cooldown_ms);
+    }
+    config::save_config(&app, &config, "set_action_cooldown")
+}
+
+/// Shortcuts registered by try_register_shortcut, by shortcut id
+#[derive(Default)]
+pub struct TestShortcutState {
+    /// Registration generation per shortcut, so a stale cleanup can't drop a newer test
+    registered: Mutex<HashMap<u32, u64>>,
+    generation: AtomicU64,
+}
+
+/// Register a combo temporarily so a settings UI can check it works before saving it
+///
+/// Fails if the combo doesn't parse, is already registered by Lens, or the OS refuses it
+/// (usually because another app holds it). While registered, pressing it emits
+/// `test-shortcut-pressed`. It's unregistered by try_unregister_shortcut, or automatically
+/// after a minute so an abandoned dialog doesn't leave a phantom binding behind.
+#[tauri::command]
+pub fn try_register_shortcut(
+    app: AppHandle,
+    state: State<'_, TestShortcutState>,
+    shortcut: String,
+) -> Result<(), String> {
+    let parsed = shortcut.parse::<Shortcut>().map_err(|e| e.to_string())?;
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(parsed) {
+        return Err(format!("{} is already registered by Lens", shortcut));
+    }
+    global_shortcut
+        .register(parsed)
+        .map_err(|e| e.to_string())?;
+
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    state
+        .registered
+        .lock()
+        .unwrap()
+        .insert(parsed.id(), generation);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(TEST_SHORTCUT_TIMEOUT);
+        let state = app.state::<TestShortcutState>();
+        let mut registered = state.registered.lock().unwrap();
+        if registered.get(&parsed.id()) == Some(&generation) {
+            registered.remove(&parsed.id());
+            let _ = app.global_shortcut().unregister(parsed);
+        }
+    });
+    Ok(())
+}
+
+/// Undo a try_register_shortcut; real bindings are never touched
+#[tauri::command]
+pub fn try_unregister_shortcut(
+    app: AppHandle,
+    state: State<'_, TestShortcutState>,
+    shortcut: String,
+) -> Result<(), String> {
+    let parsed = shortcut.parse::<Shortcut>().map_err(|e| e.to_string())?;
+    if state
+        .registered
+        .lock()
+        .unwrap()
+        .remove(&parsed.id())
+        .is_none()
+    {
+        return Err(format!("{} is not a test registration", shortcut));
+    }
+    app.global_shortcut()
+        .unregister(parsed)
+        .map_err(|e| e.to_string())
+}
+
+/// Handle a press of a try_register_shortcut combo; returns true if it was consumed
+pub fn handle_test_shortcut(app: &AppHandle, shortcut: &Shortcut) -> bool {
+    let state = app.state::<TestShortcutState>();
+    if !state
+        .registered
+        .lock()
+        .unwrap()
+        .contains_key(&shortcut.id())
+    {
+        return false;
+    }
+    let _ = app.emit("test-shortcut-pressed", shortcut.into_string());
+    true
+}
+
+/// Run the behavior bound to a shortcut action, exactly as if its hotkey was pressed
+pub fn run_action(app: &AppHandle, action: &str) -> Result<(), String> {
+    super::idle::record_activity(app);
+
+    match action {
+        "toggle" => {
+            let _ = app.emit("toggle-window-triggered", ());
+        }
+        "ask" => {
+            // The shortcut is only registered while hidden when ask_shows_window is on
+            if let Some(window) = app.get_webview_window("main") {
+                let is_focused = window.is_focused().unwrap_or(false);
+                let visible = window.is_visible().unwrap_or(false);
+                if !visible && config::load_config(app).ask_shows_window {
+                    super::toggle_window(app.clone());
+                }
+                let _ = window.set_focus();
+                let _ = app.emit("ask-triggered", !is_focused);
+            }
+        }
+        "screen_share" => run_screen_share(app, CaptureTarget::FullScreen)?,
+        "quick_note" => toggle_quick_note(app.clone())?,
+        _ => return Err(format!("Unknown action: {}", action)),
+    }
+    Ok(())
+}
+
+/// Modifier names accepted by the shortcut parser, in canonical form
+const SUPPORTED_MODIFIERS: &[&str] = &["CommandOrControl", "Control", "Alt", "Shift", "Super"];
+
+/// Keys accepted by the shortcut parser; their Display form is what it expects
+const SUPPORTED_KEYS: &[Code] = &[
+    Code::Backquote,
+    Code::Backslash,
+    Code::BracketLeft,
+    Code::BracketRight,
+    Code::Pause,
+    Code::Comma,
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+    Code::Equal,
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+    Code::Minus,
+    Code::Period,
+    Code::Quote,
+    Code::Semicolon,
+    Code::Slash,
+    Code::Backspace,
+    Code::CapsLock,
+    Code::Enter,
+    Code::Space,
+    Code::Tab,
+    Code::Delete,
+    Code::End,
+    Code::Home,
+    Code::Insert,
+    Code::PageDown,
+    Code::PageUp,
+    Code::PrintScreen,
+    Code::ScrollLock,
+    Code::ArrowDown,
+    Code::ArrowLeft,
+    Code::ArrowRight,
+    Code::ArrowUp,
+    Code::NumLock,
+    Code::Numpad0,
+    Code::Numpad1,
+    Code::Numpad2,
+    Code::Numpad3,
+    Code::Numpad4,
+    Code::Numpad5,
+    Code::Numpad6,
+    Code::Numpad7,
+    Code::Numpad8,
+    Code::Numpad9,
+    Code::NumpadAdd,
+    Code::NumpadDecimal,
+    Code::NumpadDivide,
+    Code::NumpadEnter,
+    Code::NumpadEqual,
+    Code::NumpadMultiply,
+    Code::NumpadSubtract,
+    Code::Escape,
+    Code::F1,
+    Code::F2,
+    Code::F3,
+    Code::F4,
+    Code::F5,
+    Code::F6,
+    Code::F7,
+    Code::F8,
+    Code::F9,
+    Code::F10,
+    Code::F11,
+    Code::F12,
+    Code::F13,
+    Code::F14,
+    Code::F15,
+    Code::F16,
+    Code::F17,
+    Code::F18,
+    Code::F19,
+    Code::F20,
+    Code::F21,
+    Code::F22,
+    Code::F23,
+    Code::F24,
+    Code::AudioVolumeDown,
+    Code::AudioVolumeUp,
+    Code::AudioVolumeMute,
+    Code::MediaPlay,
+    Code::MediaPause,
+    Code::MediaPlayPause,
+    Code::MediaStop,
+    Code::MediaTrackNext,
+    Code::MediaTrackPrevious,
+];
+
+/// Valid modifier and key tokens for building a shortcut picker
+#[derive(Debug, Serialize, Clone)]
+pub struct ShortcutTokens {
+    pub modifiers: Vec<String>,
+    pub keys: Vec<String>,
+}
+
+/// List the modifier and key names the shortcut parser accepts
+#[tauri::command]
+pub fn get_shortcut_tokens() -> ShortcutTokens {
+    ShortcutTokens {
+        modifiers: SUPPORTED_MODIFIERS.iter().map(|m| m.to_string()).collect(),
+        keys: SUPPORTED_KEYS.iter().map(|code| code.to_string()).collect(),
+    }
+}
+
+/// Trigger a shortcut action without pressing its keys (UI tests, macros)
+#[tauri::command]
+pub fn trigger_action(app: AppHandle, action: String) -> Result<(), String> {
+    run_action(&app, &action)
+}
+
 #[tauri::command]
 pub fn get_shortcuts(app: AppHandle) -> ShortcutsConfig {
     let config = config::load_config(&app);
     config.shortcuts
 }
 
+/// Parse every combo in `shortcuts`, in binding order, failing malformed ones and any
+/// combo already bound earlier in the list
+fn check_bindings(
+    shortcuts: &ShortcutsConfig,
+) -> Vec<(&'static str, &String, Result<Shortcut, String>)> {
+    let mut seen: Vec<(&str, Shortcut)> = Vec::new();
+    let mut checked = Vec::new();
+    for (action, combos) in shortcuts.bindings() {
+        for combo in combos {
+            let result = combo
+                .parse::<Shortcut>()
+                .map_err(|e| e.to_string())
+                .and_then(
+                    |shortcut| match seen.iter().find(|(_, s)| s.id() == shortcut.id()) {
+                        Some((other, _)) if *other == action => {
+                            Err(format!("{} is bound to {} more than once", combo, action))
+                        }
+                        Some((other, _)) => Err(format!(
+                            "{} is bound to both {} and {}",
+                            combo, other, action
+                        )),
+                        None => {
+                            seen.push((action, shortcut));
+                            Ok(shortcut)
+                        }
+                    },
+                );
+            checked.push((action, combo, result));
+        }
+    }
+    checked
+}
+
+/// Result of validating one combo
+#[derive(Debug, Serialize, Clone)]
+pub struct ComboValidation {
+    pub combo: String,
+    /// Why the combo can't be used, or None if it can
+    pub error: Option<String>,
+}
+
+/// Results for every combo bound to one action
+#[derive(Debug, Serialize, Clone)]
+pub struct ActionValidation {
+    pub action: String,
+    pub combos: Vec<ComboValidation>,
+    pub valid: bool,
+}
+
+/// Outcome of validate_shortcuts
+#[derive(Debug, Serialize, Clone)]
+pub struct ValidationReport {
+    pub actions: Vec<ActionValidation>,
+    /// Whether set_shortcuts would accept the whole config
+    pub valid: bool,
+}
+
+/// Check a whole ShortcutsConfig the way set_shortcuts would, without applying it
+///
+/// Every combo is parsed and checked against the others; with `probe_os`, combos Lens
+/// doesn't already hold are also checked against other apps by registering them for an
+/// instant, as check_shortcut_conflict does. Either way the registered set is left exactly
+/// as it was.
 #[tauri::command]
-pub fn set_shortcuts(app: AppHandle, new_shortcuts: ShortcutsConfig) -> Result<(), String> {
-    let mut config = config::load_config(&app);
-    let old_shortcuts = config.shortcuts.clone();
+pub fn validate_shortcuts(
+    app: AppHandle,
+    config: ShortcutsConfig,
+    probe_os: Option<bool>,
+) -> ValidationReport {
+    let probe_os = probe_os.unwrap_or(false);
+    validation_report(&config, |shortcut| {
+        probe_os && registered_elsewhere(&app, shortcut) == Some(true)
+    })
+}
 
-    // Validate all new shortcuts first
-    let new_toggle = new_shortcuts
-        .toggle
-        .parse::<Shortcut>()
-        .map_err(|e| e.to_string())?;
-    let new_ask = new_shortcuts
-        .ask
-        .parse::<Shortcut>()
-        .map_err(|e| e.to_string())?;
-    let new_screen_share = new_shortcuts
-        .screen_share
-        .parse::<Shortcut>()
-        .map_err(|e| e.to_string())?;
+/// Build validate_shortcuts' report, asking `taken` whether another app holds a combo
+fn validation_report(
+    config: &ShortcutsConfig,
+    mut taken: impl FnMut(Shortcut) -> bool,
+) -> ValidationReport {
+    let mut actions: Vec<ActionValidation> = config
+        .bindings()
+        .into_iter()
+        .map(|(action, _)| ActionValidation {
+            action: action.to_string(),
+            combos: Vec::new(),
+            valid: true,
+        })
+        .collect();
 
-    // Unregister old shortcuts
-    if let Ok(s) = old_shortcuts.toggle.parse::<Shortcut>() {
-        let _ = app.global_shortcut().unregister(s);
+    for (action, combo, result) in check_bindings(config) {
+        let error = match result {
+            Ok(shortcut) if taken(shortcut) => {
+                Some(format!("{} is already registered by another app", combo))
+            }
+            Ok(_) => None,
+            Err(e) => Some(e),
+        };
+        if let Some(entry) = actions.iter_mut().find(|a| a.action == action) {
+            entry.valid &= error.is_none();
+            entry.combos.push(ComboValidation {
+                combo: combo.clone(),
+                error,
+            });
+        }
     }
-    if let Ok(s) = old_shortcuts.ask.parse::<Shortcut>() {
-        let _ = app.global_shortcut().unregister(s);
+
+    ValidationReport {
+        valid: actions.iter().all(|a| a.valid),
+        actions,
     }
-    if let Ok(s) = old_shortcuts.screen_share.parse::<Shortcut>() {
-        let _ = app.global_shortcut().unregister(s);
+}
+
+/// Swap the registered shortcuts from `config.shortcuts` to `new_shortcuts`
+///
+/// Every combo is parsed before anything is unregistered. If any registration fails, the
+/// new combos are unregistered again and the previous set is restored, leaving `config`
+/// untouched. The screen_share modifier variants move along with the bindings, but are only
+/// registered while the main window is visible.
+pub(crate) fn replace_shortcuts(
+    app: &AppHandle,
+    config: &mut AppConfig,
+    new_shortcuts: ShortcutsConfig,
+) -> Result<(), String> {
+    let errors = swap_shortcuts(
+        app.global_shortcut(),
+        config,
+        new_shortcuts,
+        main_window_visible(app),
+    )?;
+    if !errors.is_empty() {
+        last_errors::record_error(app, Subsystem::Shortcuts, errors.join(", "));
     }
+    Ok(())
+}
 
-    // Update config
-    config.shortcuts = new_shortcuts;
+/// replace_shortcuts against any registry, returning the capture variants that failed to
+/// register
+fn swap_shortcuts(
+    registry: &impl ShortcutRegistry,
+    config: &mut AppConfig,
+    new_shortcuts: ShortcutsConfig,
+    visible: bool,
+) -> Result<Vec<String>, String> {
+    // Validate all new shortcuts first, rejecting combos bound more than once
+    let mut new_parsed: Vec<(&str, Shortcut)> = Vec::new();
+    for (action, _, shortcut) in check_bindings(&new_shortcuts) {
+        new_parsed.push((action, shortcut?));
+    }
+
+    // Unregister old shortcuts
+    let old_parsed: Vec<Shortcut> = config
+        .shortcuts
+        .bindings()
+        .iter()
+        .flat_map(|(_, combos)| combos.iter())
+        .filter_map(|combo| combo.parse::<Shortcut>().ok())
+        .collect();
+    let old_variants = capture_variants(config);
+    for shortcut in old_parsed
+        .iter()
+        .chain(old_variants.iter().map(|(shortcut, _)| shortcut))
+    {
+        registry.unregister(*shortcut);
+    }
 
     // Register new shortcuts
-    // If any registration fails, we should probably attempt to revert?
-    // For now, let's just try to register all and report error if any.
+    let mut registered = Vec::new();
     let mut errors = Vec::new();
+    for (action, shortcut) in new_parsed {
+        match registry.register(shortcut) {
+            Ok(()) => registered.push(shortcut),
+            Err(e) => errors.push(format!(
+                "Failed to register {}: {}",
+                action.replace('_', " "),
+                e
+            )),
+        }
+    }
 
-    if let Err(e) = app.global_shortcut().register(new_toggle) {
-        errors.push(format!("Failed to register toggle: {}", e));
+    if !errors.is_empty() {
+        // Roll back to the previous set
+        for shortcut in registered {
+            registry.unregister(shortcut);
+        }
+        for shortcut in old_parsed {
+            let _ = registry.register(shortcut);
+        }
+        if visible {
+            for (shortcut, _) in old_variants {
+                let _ = registry.register(shortcut);
+            }
+        }
+        return Err(errors.join(", "));
     }
-    if let Err(e) = app.global_shortcut().register(new_ask) {
-        errors.push(format!("Failed to register ask: {}", e));
+
+    config.shortcuts = new_shortcuts;
+
+    let mut variant_errors = Vec::new();
+    if visible {
+        for (shortcut, _) in capture_variants(config) {
+            if let Err(e) = registry.register(shortcut) {
+                variant_errors.push(format!("{}: {}", shortcut, e));
+            }
+        }
     }
-    if let Err(e) = app.global_shortcut().register(new_screen_share) {
-        errors.push(format!("Failed to register screen share: {}", e));
+    Ok(variant_errors)
+}
+
+/// Whether the main window is showing, and so the optional shortcuts should be registered
+fn main_window_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Outcome of re-registering one action's shortcuts
+#[derive(Debug, Serialize, Clone)]
+pub struct ShortcutRegistration {
+    pub action: String,
+    pub registered: bool,
+    pub error: Option<String>,
+}
+
+/// Drop every global shortcut and register the configured ones again
+///
+/// The OS can silently drop registrations across sleep/wake. Optional actions are only
+/// re-registered while the main window is visible, matching register_optional_shortcuts.
+pub fn reregister_all_shortcuts(app: &AppHandle) -> Vec<ShortcutRegistration> {
+    let config = config::load_config(app);
+    let visible = main_window_visible(app);
+
+    let _ = app.global_shortcut().unregister_all();
+
+    let mut registrations: Vec<ShortcutRegistration> = config
+        .shortcuts
+        .bindings()
+        .into_iter()
+        .filter(|(action, _)| visible || always_registered(&config, action))
+        .map(|(action, combos)| register_action(app, action, combos))
+        .collect();
+    if let Some(combo) = &config.peek_shortcut {
+        registrations.push(register_action(app, "peek", std::slice::from_ref(combo)));
     }
+    if visible {
+        for (shortcut, target) in capture_variants(&config) {
+            let error = app.global_shortcut().register(shortcut).err();
+            registrations.push(ShortcutRegistration {
+                action: format!("screen_share ({:?})", target),
+                registered: error.is_none(),
+                error: error.map(|e| format!("{}: {}", shortcut, e)),
+            });
+        }
+    }
+    track_registration_errors(
+        app,
+        registrations
+            .iter()
+            .filter_map(|r| r.error.clone())
+            .collect(),
+    );
+    registrations
+}
 
-    if !errors.is_empty() {
-        return Err(errors.join(", "));
+fn register_action(app: &AppHandle, action: &str, combos: &[String]) -> ShortcutRegistration {
+    let errors: Vec<String> = combos
+        .iter()
+        .filter_map(|combo| {
+            let result = combo
+                .parse::<Shortcut>()
+                .map_err(|e| e.to_string())
+                .and_then(|shortcut| {
+                    app.global_shortcut()
+                        .register(shortcut)
+                        .map_err(|e| e.to_string())
+                });
+            result.err().map(|e| format!("{}: {}", combo, e))
+        })
+        .collect();
+    ShortcutRegistration {
+        action: action.to_string(),
+        registered: errors.is_empty(),
+        error: (!errors.is_empty()).then(|| errors.join(", ")),
     }
+}
+
+/// Recover hotkeys that stopped working (e.g. after sleep) without restarting the app
+#[tauri::command]
+pub fn reregister_shortcuts(app: AppHandle) -> Vec<ShortcutRegistration> {
+    reregister_all_shortcuts(&app)
+}
+
+/// Choose whether the ask shortcut also works while hidden, showing the overlay first
+#[tauri::command]
+pub fn set_ask_shows_window(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.ask_shows_window = enabled;
+    config::save_config(&app, &config, "set_ask_shows_window")?;
+    reregister_all_shortcuts(&app);
+    Ok(())
+}
 
-    config::save_config(&app, &config)?;
+#[tauri::command]
+pub fn set_shortcuts(app: AppHandle, new_shortcuts: ShortcutsConfig) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    replace_shortcuts(&app, &mut config, new_shortcuts)?;
+    config.sync_active_profile();
+
+    config::save_config(&app, &config, "set_shortcuts")?;
 
     // Notify frontend to update UI
     let _ = app.emit("shortcuts-changed", ());
@@ -106,6 +936,73 @@ pub fn set_shortcuts(app: AppHandle, new_shortcuts: ShortcutsConfig) -> Result<(
     Ok(())
 }
 
+/// How a proposed shortcut collides with existing bindings
+#[derive(Debug, Serialize, Clone)]
+pub struct ConflictResult {
+    /// The other Lens action already bound to the combo
+    pub conflicting_action: Option<String>,
+    /// Whether another app holds the combo, or None when that can't be probed
+    pub registered_elsewhere: Option<bool>,
+}
+
+/// The action other than `action` that `shortcut` is already bound to
+fn conflicting_action(
+    config: &AppConfig,
+    action: &str,
+    shortcut: &Shortcut,
+) -> Option<&'static str> {
+    config
+        .shortcuts
+        .bindings()
+        .into_iter()
+        .filter(|(other, _)| *other != action)
+        .find(|(_, combos)| {
+            combos
+                .iter()
+                .filter_map(|combo| combo.parse::<Shortcut>().ok())
+                .any(|s| s.id() == shortcut.id())
+        })
+        .map(|(other, _)| other)
+}
+
+/// Check whether binding `shortcut` to `action` would clash, for a settings UI to call as
+/// the user types
+///
+/// Another app holding the combo is found by briefly registering it. That can't be probed
+/// while Lens itself has the combo registered, so registered_elsewhere is None then.
+#[tauri::command]
+pub fn check_shortcut_conflict(
+    app: AppHandle,
+    action: String,
+    shortcut: String,
+) -> Result<ConflictResult, String> {
+    let mut config = config::load_config(&app);
+    if config.shortcuts.action_mut(&action).is_none() {
+        return Err(format!("Unknown action: {}", action));
+    }
+    let parsed = shortcut.parse::<Shortcut>().map_err(|e| e.to_string())?;
+
+    Ok(ConflictResult {
+        conflicting_action: conflicting_action(&config, &action, &parsed).map(str::to_string),
+        registered_elsewhere: registered_elsewhere(&app, parsed),
+    })
+}
+
+/// Whether another app holds `shortcut`, found by registering it for an instant
+///
+/// None when Lens itself has it registered, since then there's nothing to probe.
+fn registered_elsewhere(app: &AppHandle, shortcut: Shortcut) -> Option<bool> {
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(shortcut) {
+        return None;
+    }
+    let taken = global_shortcut.register(shortcut).is_err();
+    if !taken {
+        let _ = global_shortcut.unregister(shortcut);
+    }
+    Some(taken)
+}
+
 #[tauri::command]
 pub fn update_shortcut(app: AppHandle, action: String, new_shortcut: String) -> Result<(), String> {
     // This function can be deprecated or kept for single updates if needed.
@@ -116,13 +1013,10 @@ pub fn update_shortcut(app: AppHandle, action: String, new_shortcut: String) ->
     // The UI should call set_shortcuts only on save.
     // So this function is effectively unused by the new UI flow, but kept for API completeness or backward compat.
 
+    // Replaces all of the action's combos with the single new one
     let mut config = config::load_config(&app);
-    let old_shortcut_str = match action.as_str() {
-        "toggle" => config.shortcuts.toggle.clone(),
-        "ask" => config.shortcuts.ask.clone(),
-        "screen_share" => config.shortcuts.screen_share.clone(),
-        _ => return Err("Invalid action".to_string()),
-    };
+    let mut new_shortcuts = config.shortcuts.clone();
+    *new_shortcuts.action_mut(&action).ok_or("Invalid action")? = vec![new_shortcut.clone()];
 
     // Validate new shortcut
     let new_parsed = new_shortcut
@@ -130,35 +1024,265 @@ pub fn update_shortcut(app: AppHandle, action: String, new_shortcut: String) ->
         .map_err(|e| e.to_string())?;
 
     // Check for conflicts
-    if (action != "toggle" && config.shortcuts.toggle == new_shortcut)
-        || (action != "ask" && config.shortcuts.ask == new_shortcut)
-        || (action != "screen_share" && config.shortcuts.screen_share == new_shortcut)
-    {
+    if conflicting_action(&config, &action, &new_parsed).is_some() {
         return Err("Shortcut already in use".to_string());
     }
 
-    // Unregister old shortcut
-    if let Ok(old_parsed) = old_shortcut_str.parse::<Shortcut>() {
-        let _ = app.global_shortcut().unregister(old_parsed);
+    // Swap the registrations, including the screen_share modifier variants
+    replace_shortcuts(&app, &mut config, new_shortcuts)?;
+    config.sync_active_profile();
+
+    config::save_config(&app, &config, "update_shortcut")?;
+
+    // Notify frontend to update UI
+    let _ = app.emit("shortcuts-changed", ());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+
+    /// Stands in for the OS hotkey registry
+    #[derive(Default)]
+    struct FakeRegistry(RefCell<HashSet<u32>>);
+
+    impl FakeRegistry {
+        fn is_registered(&self, shortcut: Shortcut) -> bool {
+            self.0.borrow().contains(&shortcut.id())
+        }
+    }
+
+    impl ShortcutRegistry for FakeRegistry {
+        fn register(&self, shortcut: Shortcut) -> Result<(), String> {
+            self.0.borrow_mut().insert(shortcut.id());
+            Ok(())
+        }
+
+        fn unregister(&self, shortcut: Shortcut) {
+            self.0.borrow_mut().remove(&shortcut.id());
+        }
+
+        fn unregister_all(&self) {
+            self.0.borrow_mut().clear();
+        }
     }
 
-    // Update config
-    match action.as_str() {
-        "toggle" => config.shortcuts.toggle = new_shortcut.clone(),
-        "ask" => config.shortcuts.ask = new_shortcut.clone(),
-        "screen_share" => config.shortcuts.screen_share = new_shortcut.clone(),
-        _ => {}
+    fn parse(combo: &str) -> Shortcut {
+        combo.parse().unwrap()
     }
 
-    // Register new shortcut
-    if let Err(e) = app.global_shortcut().register(new_parsed) {
-        return Err(format!("Failed to register shortcut: {}", e));
+    #[test]
+    fn teardown_leaves_nothing_registered() {
+        let config = AppConfig::default();
+        let registry = FakeRegistry::default();
+        let mut registered: Vec<Shortcut> = config
+            .shortcuts
+            .bindings()
+            .into_iter()
+            .flat_map(|(_, combos)| combos.iter().map(|combo| parse(combo)))
+            .collect();
+        registered.extend(capture_variants(&config).into_iter().map(|(s, _)| s));
+        // Something only unregister_all catches, like the region selection Escape
+        registered.push(Shortcut::new(None, Code::Escape));
+        for shortcut in &registered {
+            registry.register(*shortcut).unwrap();
+        }
+
+        release_shortcuts(&config, &registry);
+
+        for shortcut in registered {
+            assert!(
+                !registry.is_registered(shortcut),
+                "{} still registered",
+                shortcut
+            );
+        }
     }
 
-    config::save_config(&app, &config)?;
+    fn config_with_variants() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.capture.modifier_targets = vec![crate::config::ModifierTarget {
+            modifier: "Shift".to_string(),
+            target: CaptureTarget::Region,
+        }];
+        config
+    }
 
-    // Notify frontend to update UI
-    let _ = app.emit("shortcuts-changed", ());
+    #[test]
+    fn replacing_shortcuts_moves_capture_variants() {
+        let mut config = config_with_variants();
+        let registry = FakeRegistry::default();
+        let old_variants: Vec<Shortcut> = capture_variants(&config)
+            .into_iter()
+            .map(|(s, _)| s)
+            .collect();
+        for shortcut in &old_variants {
+            registry.register(*shortcut).unwrap();
+        }
 
-    Ok(())
+        let mut new_shortcuts = config.shortcuts.clone();
+        new_shortcuts.screen_share = vec!["CommandOrControl+D".to_string()];
+        let errors = swap_shortcuts(&registry, &mut config, new_shortcuts, true).unwrap();
+
+        assert!(errors.is_empty());
+        for shortcut in old_variants {
+            assert!(
+                !registry.is_registered(shortcut),
+                "{} still registered",
+                shortcut
+            );
+        }
+        assert!(registry.is_registered(parse("CommandOrControl+Shift+D")));
+    }
+
+    #[test]
+    fn capture_variants_wait_for_the_window_to_show() {
+        let mut config = config_with_variants();
+        let registry = FakeRegistry::default();
+
+        let mut new_shortcuts = config.shortcuts.clone();
+        new_shortcuts.screen_share = vec!["CommandOrControl+D".to_string()];
+        swap_shortcuts(&registry, &mut config, new_shortcuts, false).unwrap();
+
+        assert!(registry.is_registered(parse("CommandOrControl+D")));
+        assert!(!registry.is_registered(parse("CommandOrControl+Shift+D")));
+    }
+
+    #[test]
+    fn malformed_combos_in_config_are_skipped() {
+        let mut config = config_with_variants();
+        config.shortcuts.toggle = vec!["Ctrl+Nope".to_string(), "".to_string()];
+        config.shortcuts.ask = vec!["+++".to_string(), "CommandOrControl+Enter".to_string()];
+
+        assert_eq!(binding_for(&config, &parse("Ctrl+N")), None);
+        assert_eq!(
+            binding_for(&config, &parse("CommandOrControl+Enter")),
+            Some(ShortcutBinding::Action("ask"))
+        );
+        assert_eq!(
+            binding_for(&config, &parse("CommandOrControl+Shift+S")),
+            Some(ShortcutBinding::Capture(CaptureTarget::Region))
+        );
+    }
+
+    #[test]
+    fn only_the_first_press_of_a_hold_gets_through() {
+        let mut tracker = KeyRepeatTracker::default();
+        let start = Instant::now();
+        let tick = Duration::from_millis(30);
+
+        assert!(!tracker.observe(1, ShortcutState::Pressed, start));
+        assert!(tracker.observe(1, ShortcutState::Pressed, start + tick));
+        assert!(tracker.observe(1, ShortcutState::Pressed, start + tick * 2));
+        assert!(!tracker.observe(1, ShortcutState::Released, start + tick * 3));
+        assert!(!tracker.observe(1, ShortcutState::Pressed, start + tick * 4));
+    }
+
+    #[test]
+    fn cooldown_runs_out() {
+        let mut tracker = CooldownTracker::default();
+        let start = Instant::now();
+        let cooldown = Duration::from_millis(500);
+
+        assert_eq!(tracker.remaining("screen_share", cooldown, start), None);
+        tracker.record("screen_share", start);
+        assert_eq!(
+            tracker.remaining("screen_share", cooldown, start + Duration::from_millis(100)),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(tracker.remaining("ask", cooldown, start), None);
+        assert_eq!(
+            tracker.remaining("screen_share", cooldown, start + cooldown),
+            None
+        );
+    }
+
+    #[test]
+    fn shortcuts_are_tracked_separately() {
+        let mut tracker = KeyRepeatTracker::default();
+        let now = Instant::now();
+
+        assert!(!tracker.observe(1, ShortcutState::Pressed, now));
+        assert!(!tracker.observe(2, ShortcutState::Pressed, now));
+        tracker.observe(2, ShortcutState::Released, now);
+        assert!(tracker.observe(1, ShortcutState::Pressed, now));
+    }
+
+    #[test]
+    fn press_after_a_lost_release_is_fresh() {
+        let mut tracker = KeyRepeatTracker::default();
+        let start = Instant::now();
+
+        assert!(!tracker.observe(1, ShortcutState::Pressed, start));
+        let later = start + KEY_REPEAT_WINDOW + Duration::from_millis(1);
+        assert!(!tracker.observe(1, ShortcutState::Pressed, later));
+    }
+
+    fn shortcuts(toggle: &[&str], ask: &[&str]) -> ShortcutsConfig {
+        ShortcutsConfig {
+            toggle: toggle.iter().map(|c| c.to_string()).collect(),
+            ask: ask.iter().map(|c| c.to_string()).collect(),
+            screen_share: vec!["CommandOrControl+Shift+S".to_string()],
+            quick_note: vec!["CommandOrControl+Shift+N".to_string()],
+        }
+    }
+
+    fn errors(report: &ValidationReport, action: &str) -> Vec<Option<String>> {
+        let entry = report.actions.iter().find(|a| a.action == action).unwrap();
+        entry.combos.iter().map(|c| c.error.clone()).collect()
+    }
+
+    #[test]
+    fn default_shortcuts_are_valid() {
+        let report = validation_report(&ShortcutsConfig::default(), |_| false);
+        assert!(report.valid);
+        assert!(report.actions.iter().all(|a| a.valid));
+    }
+
+    #[test]
+    fn duplicate_combos_are_reported() {
+        let config = shortcuts(&["Alt+Space", "Alt+Space"], &["Alt+Space", "Alt+Enter"]);
+        let report = validation_report(&config, |_| false);
+
+        assert!(!report.valid);
+        let toggle = errors(&report, "toggle");
+        assert_eq!(toggle[0], None);
+        assert!(toggle[1].as_deref().unwrap().contains("more than once"));
+        let ask = errors(&report, "ask");
+        assert!(ask[0].as_deref().unwrap().contains("both toggle and ask"));
+        assert_eq!(ask[1], None);
+    }
+
+    #[test]
+    fn malformed_combos_are_reported() {
+        let config = shortcuts(&["Alt+Space"], &["Control+", "Shift+Nope", "Alt+Enter"]);
+        let report = validation_report(&config, |_| false);
+
+        assert!(!report.valid);
+        let ask = errors(&report, "ask");
+        assert!(ask[0].is_some());
+        assert!(ask[1].is_some());
+        assert_eq!(ask[2], None);
+        assert!(report
+            .actions
+            .iter()
+            .any(|a| a.action == "toggle" && a.valid));
+    }
+
+    #[test]
+    fn combos_taken_by_other_apps_are_reported() {
+        let config = shortcuts(&["Alt+Space"], &["Alt+Enter"]);
+        let taken = parse("Alt+Enter").id();
+        let report = validation_report(&config, |shortcut| shortcut.id() == taken);
+
+        assert!(!report.valid);
+        assert!(errors(&report, "ask")[0]
+            .as_deref()
+            .unwrap()
+            .contains("another app"));
+    }
 }
@@ -0,0 +1,82 @@
+use crate::capture::TEMP_CAPTURE_PREFIX;
+use crate::config;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+/// Disk space used by Lens, in bytes
+#[derive(Debug, Serialize, Clone)]
+pub struct StorageInfo {
+    pub config_dir: Option<String>,
+    pub config_bytes: u64,
+    pub log_dir: Option<String>,
+    pub log_bytes: u64,
+    /// Temp captures not yet cleaned up
+    pub temp_capture_bytes: u64,
+}
+
+/// Total size of the files under `dir`, following subdirectories
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Report how much space the config, logs and leftover captures take up
+#[tauri::command]
+pub fn get_storage_info(app: AppHandle) -> StorageInfo {
+    let config_dir = config::get_config_path(&app).and_then(|p| p.parent().map(Path::to_path_buf));
+    let log_dir = app.path().app_log_dir().ok();
+
+    let temp_capture_bytes = fs::read_dir(std::env::temp_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    entry
+                        .file_name()
+                        .to_string_lossy()
+                        .starts_with(TEMP_CAPTURE_PREFIX)
+                })
+                .filter_map(|entry| entry.metadata().ok())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0);
+
+    StorageInfo {
+        config_bytes: config_dir.as_deref().map(dir_size).unwrap_or(0),
+        config_dir: config_dir.map(|p| p.to_string_lossy().into_owned()),
+        log_bytes: log_dir.as_deref().map(dir_size).unwrap_or(0),
+        log_dir: log_dir.map(|p| p.to_string_lossy().into_owned()),
+        temp_capture_bytes,
+    }
+}
+
+/// Delete the log files in the app log dir, returning the number of bytes freed
+#[tauri::command]
+pub fn clear_logs(app: AppHandle) -> Result<u64, String> {
+    let log_dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    let Ok(entries) = fs::read_dir(&log_dir) else {
+        return Ok(0);
+    };
+
+    let freed = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let len = entry.metadata().ok()?.len();
+            fs::remove_file(entry.path()).ok().map(|_| len)
+        })
+        .sum();
+    Ok(freed)
+}
@@ -0,0 +1,75 @@
+use tauri::image::Image;
+use tauri::AppHandle;
+
+use super::TRAY_ID;
+use crate::config;
+
+/// Largest tray icon file accepted, in bytes
+const MAX_ICON_BYTES: u64 = 1024 * 1024;
+/// Largest tray icon accepted, in pixels per side; the OS scales it down to the tray anyway
+const MAX_ICON_SIDE: u32 = 512;
+
+/// Show the image at `path` as the tray icon and keep it across restarts
+///
+/// The image has to be a PNG or BMP of at most 1 MB and 512x512 pixels.
+#[tauri::command]
+pub fn set_tray_icon(app: AppHandle, path: String) -> Result<(), String> {
+    let icon = load_icon(&path)?;
+    let tray = app.tray_by_id(TRAY_ID).ok_or("Tray icon not found")?;
+    tray.set_icon(Some(icon)).map_err(|e| e.to_string())?;
+
+    let mut config = config::load_config(&app);
+    config.tray_icon_path = Some(path);
+    config::save_config(&app, &config, "set_tray_icon")
+}
+
+/// Go back to the app icon in the tray
+#[tauri::command]
+pub fn reset_tray_icon(app: AppHandle) -> Result<(), String> {
+    let tray = app.tray_by_id(TRAY_ID).ok_or("Tray icon not found")?;
+    tray.set_icon(app.default_window_icon().cloned())
+        .map_err(|e| e.to_string())?;
+
+    let mut config = config::load_config(&app);
+    config.tray_icon_path = None;
+    config::save_config(&app, &config, "reset_tray_icon")
+}
+
+/// The icon the tray starts with: the saved custom one, or the app icon if there is none or
+/// it can no longer be loaded
+pub fn startup_icon(app: &AppHandle, config: &config::AppConfig) -> Option<Image<'static>> {
+    if let Some(path) = &config.tray_icon_path {
+        match load_icon(path) {
+            Ok(icon) => return Some(icon),
+            Err(e) => eprintln!("Failed to load tray icon {}: {}", path, e),
+        }
+    }
+    app.default_window_icon()
+        .map(|icon| icon.clone().to_owned())
+}
+
+fn load_icon(path: &str) -> Result<Image<'static>, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_ICON_BYTES {
+        return Err(format!(
+            "Tray icon is {} bytes; the limit is {}",
+            size, MAX_ICON_BYTES
+        ));
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Unsupported tray icon image: {}", e))?;
+    if image.width() > MAX_ICON_SIDE || image.height() > MAX_ICON_SIDE {
+        return Err(format!(
+            "Tray icon is {}x{}; the limit is {}x{}",
+            image.width(),
+            image.height(),
+            MAX_ICON_SIDE,
+            MAX_ICON_SIDE
+        ));
+    }
+
+    let rgba = image.into_rgba8();
+    let (width, height) = rgba.dimensions();
+    Ok(Image::new_owned(rgba.into_raw(), width, height))
+}
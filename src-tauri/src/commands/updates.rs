@@ -0,0 +1,146 @@
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Url, Webview};
+use tauri_plugin_updater::UpdaterExt;
+
+use crate::config::{self, UpdateChannel};
+
+/// Manifest attached to the rolling `beta` prerelease, published by release.yml
+const BETA_MANIFEST: &str =
+    "https://github.com/saif0200/lens-app/releases/download/beta/latest.json";
+
+/// An available update, shaped like the updater plugin's own `check` result so the
+/// frontend can wrap it in the plugin's `Update` class to download and install it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMetadata {
+    rid: tauri::ResourceId,
+    current_version: String,
+    version: String,
+    date: Option<String>,
+    body: Option<String>,
+    raw_json: serde_json::Value,
+}
+
+/// How the updater is set up in this build, for checking where updates come from and
+/// which key they must be signed with
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdaterInfo {
+    /// Endpoints from tauri.conf.json
+    pub endpoints: Vec<String>,
+    /// Manifest check_for_updates actually uses, which follows the channel
+    pub channel_endpoint: Option<String>,
+    /// Key ID of the minisign public key updates are verified against, as minisign prints it
+    pub pubkey_fingerprint: Option<String>,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+}
+
+/// Report the updater's endpoints and public key, for update diagnostics
+///
+/// Only the key's fingerprint is returned, never the key itself.
+#[tauri::command]
+pub fn get_updater_info(app: AppHandle) -> UpdaterInfo {
+    let pubkey_fingerprint = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater["pubkey"].as_str())
+        .and_then(minisign_key_id);
+    let channel = config::load_config(&app).update_channel;
+
+    UpdaterInfo {
+        endpoints: configured_endpoints(&app),
+        channel_endpoint: manifest_url(&app, channel),
+        pubkey_fingerprint,
+        current_version: app.package_info().version.to_string(),
+        channel,
+    }
+}
+
+/// The key ID of a Tauri updater public key
+///
+/// The configured key is a base64-encoded minisign public key file: a comment line, then
+/// base64 of the algorithm (2 bytes), the key ID (8 bytes, little-endian) and the key.
+fn minisign_key_id(pubkey: &str) -> Option<String> {
+    let file = BASE64_STANDARD.decode(pubkey.trim()).ok()?;
+    let file = String::from_utf8(file).ok()?;
+    let key = BASE64_STANDARD.decode(file.lines().nth(1)?.trim()).ok()?;
+    let id = key.get(2..10)?;
+    Some(
+        id.iter()
+            .rev()
+            .map(|byte| format!("{:02X}", byte))
+            .collect(),
+    )
+}
+
+/// The updater endpoints from tauri.conf.json
+fn configured_endpoints(app: &AppHandle) -> Vec<String> {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater["endpoints"].as_array())
+        .map(|endpoints| {
+            endpoints
+                .iter()
+                .filter_map(|endpoint| endpoint.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The manifest to check for `channel`; stable is whatever tauri.conf.json points at
+fn manifest_url(app: &AppHandle, channel: UpdateChannel) -> Option<String> {
+    match channel {
+        UpdateChannel::Stable => configured_endpoints(app).into_iter().next(),
+        UpdateChannel::Beta => Some(BETA_MANIFEST.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_update_channel(app: AppHandle) -> UpdateChannel {
+    config::load_config(&app).update_channel
+}
+
+/// Switch release channel; the next check_for_updates uses the new channel's manifest
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, channel: UpdateChannel) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.update_channel = channel;
+    config::save_config(&app, &config, "set_update_channel")
+}
+
+/// Check the configured channel for an update
+///
+/// The updater plugin is set up once in setup() with the endpoints from tauri.conf.json, so
+/// its own `check` always hits the stable manifest. This builds an updater per check with
+/// the channel's manifest instead, which lets the channel switch without restarting.
+#[tauri::command]
+pub async fn check_for_updates(webview: Webview) -> Result<Option<UpdateMetadata>, String> {
+    let app = webview.app_handle();
+    let channel = config::load_config(app).update_channel;
+    let endpoint = manifest_url(app, channel).ok_or("No updater endpoint configured")?;
+    let endpoint = Url::parse(&endpoint).map_err(|e| e.to_string())?;
+    let update = webview
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .and_then(|builder| builder.build())
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(update.map(|update| UpdateMetadata {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        // pub_date from the manifest, already RFC 3339
+        date: update.raw_json["pub_date"].as_str().map(str::to_string),
+        body: update.body.clone(),
+        raw_json: update.raw_json.clone(),
+        rid: webview.resources_table().add(update),
+    }))
+}
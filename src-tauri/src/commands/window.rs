@@ -1,16 +1,115 @@
+use super::quick_note::QUICK_NOTE_LABEL;
 use super::shortcuts::{register_optional_shortcuts, unregister_optional_shortcuts};
-use tauri::{AppHandle, Emitter, Manager};
+use crate::config::{self, ActivationPolicy, VibrancyMaterial, WindowConfig, WindowState};
+use crate::last_errors::{self, Subsystem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::webview::{PageLoadEvent, PageLoadPayload};
+use tauri::{
+    AppHandle, Emitter, LogicalSize, Manager, PhysicalPosition, State, Webview, WebviewWindow,
+    WindowEvent,
+};
+use tauri_runtime::ResizeDirection;
+
+/// Lowest opacity accepted, so the overlay can't be made fully invisible
+const MIN_OPACITY: f64 = 0.1;
+
+/// Size limits for the main window, in logical pixels
+pub const MIN_WINDOW_SIZE: (f64, f64) = (300.0, 100.0);
+pub const MAX_WINDOW_SIZE: (f64, f64) = (2400.0, 1600.0);
+
+/// Set between reload_window and the reloaded page finishing, so settings are reapplied once
+#[derive(Default)]
+pub struct ReloadState(AtomicBool);
+
+/// Set while the user is drag-resizing the main window, so the final size gets persisted
+#[derive(Default)]
+pub struct ResizeState {
+    dragging: AtomicBool,
+    /// Bumped when a drag starts and on every Resized event of it, so only the last one ends it
+    generation: AtomicU64,
+}
+
+/// How long a drag-resize has to go without a Resized event before it counts as finished
+const RESIZE_SETTLE: Duration = Duration::from_millis(400);
+
+/// How long start_window_resize waits for the first Resized event
+///
+/// A click on the edge that never moves produces no Resized event at all, and there's no
+/// mouse-up to hear about, so the drag is ended once this passes without one.
+const RESIZE_START_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// PendingSaveState key for the size a drag-resize left the main window at
+const WINDOW_SIZE_SAVE_KEY: &str = "window_size";
+
+/// Window edge or corner to resize from
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub enum ResizeEdge {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl From<ResizeEdge> for ResizeDirection {
+    fn from(edge: ResizeEdge) -> Self {
+        match edge {
+            ResizeEdge::North => ResizeDirection::North,
+            ResizeEdge::South => ResizeDirection::South,
+            ResizeEdge::East => ResizeDirection::East,
+            ResizeEdge::West => ResizeDirection::West,
+            ResizeEdge::NorthEast => ResizeDirection::NorthEast,
+            ResizeEdge::NorthWest => ResizeDirection::NorthWest,
+            ResizeEdge::SouthEast => ResizeDirection::SouthEast,
+            ResizeEdge::SouthWest => ResizeDirection::SouthWest,
+        }
+    }
+}
+
+/// Whether content protection is currently requested, so windows created later can inherit it
+#[derive(Default)]
+pub struct ContentProtectionState {
+    enabled: AtomicBool,
+    /// Per-window settings from set_content_protection_for, cleared by the global toggle
+    overrides: Mutex<HashMap<String, bool>>,
+}
+
+impl ContentProtectionState {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the window with this label should be protected, honoring per-window overrides
+    pub fn is_enabled_for(&self, label: &str) -> bool {
+        self.overrides
+            .lock()
+            .unwrap()
+            .get(label)
+            .copied()
+            .unwrap_or_else(|| self.is_enabled())
+    }
+}
 
 /// Toggle main window visibility
 #[tauri::command]
 pub fn toggle_window(app: AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.is_visible().map(|visible| {
+            super::idle::cancel_blur_hide(&app);
             if visible {
                 let _ = window.hide();
                 unregister_optional_shortcuts(&app);
+                super::focus::restore_frontmost_app(&app);
                 let _ = app.emit("window-hidden", ());
             } else {
+                super::focus::remember_frontmost_app(&app);
                 let _ = window.show();
                 register_optional_shortcuts(&app);
                 let _ = app.emit("window-shown", ());
@@ -19,59 +118,1241 @@ pub fn toggle_window(app: AppHandle) {
     }
 }
 
-/// Resize the main window while maintaining top-left anchor
+/// Start an OS resize drag from the given edge of the frameless main window
+///
+/// The OS enforces the min/max size during the drag, and the resulting size is saved to
+/// WindowState. Not supported on macOS, where the native resize drag isn't available; the
+/// window is made non-resizable again when the drag can't start.
 #[tauri::command]
-pub fn resize_window(app: AppHandle, width: f64, height: f64) {
-    if let Some(window) = app.get_webview_window("main") {
-        #[cfg(target_os = "macos")]
+pub fn start_window_resize(
+    app: AppHandle,
+    state: State<'_, ResizeState>,
+    edge: ResizeEdge,
+) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+
+    let _ = window.set_min_size(Some(LogicalSize::new(MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1)));
+    let _ = window.set_max_size(Some(LogicalSize::new(MAX_WINDOW_SIZE.0, MAX_WINDOW_SIZE.1)));
+    let _ = window.set_resizable(true);
+
+    state.dragging.store(true, Ordering::Relaxed);
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Err(e) = window.as_ref().window().start_resize_dragging(edge.into()) {
+        state.dragging.store(false, Ordering::Relaxed);
+        let _ = window.set_resizable(false);
+        return Err(e.to_string());
+    }
+    finish_resize_after(app, window, generation, RESIZE_START_TIMEOUT);
+    Ok(())
+}
+
+/// Finish user resizes started by start_window_resize once they settle
+///
+/// The window goes back to non-resizable, as tauri.conf.json has it, and the final size
+/// is persisted once per drag rather than on every Resized event.
+pub fn track_user_resize(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Resized(_) = event {
+            let state = handle.state::<ResizeState>();
+            if !state.dragging.load(Ordering::Relaxed) {
+                return;
+            }
+            defer_window_size_save(&handle, &tracked);
+            let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+            finish_resize_after(handle.clone(), tracked.clone(), generation, RESIZE_SETTLE);
+        }
+    });
+}
+
+/// End the drag after `delay`, unless another Resized event has come in by then
+fn finish_resize_after(app: AppHandle, window: WebviewWindow, generation: u64, delay: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let state = app.state::<ResizeState>();
+        if state.generation.load(Ordering::SeqCst) != generation
+            || !state.dragging.swap(false, Ordering::Relaxed)
         {
-            use cocoa::appkit::NSWindow;
-            use cocoa::base::id;
-            use cocoa::foundation::{NSRect, NSSize};
+            return;
+        }
+        let _ = window.set_resizable(false);
+        let _ = config::save_deferred(&app, WINDOW_SIZE_SAVE_KEY);
+    });
+}
+
+/// Queue the main window's current logical size for WindowState
+fn defer_window_size_save(app: &AppHandle, window: &WebviewWindow) {
+    let (Ok(size), Ok(scale)) = (window.inner_size(), window.scale_factor()) else {
+        return;
+    };
+    let logical = size.to_logical::<f64>(scale);
+    config::defer_save(app, WINDOW_SIZE_SAVE_KEY, move |config| {
+        config.window_state.width = Some(logical.width.clamp(MIN_WINDOW_SIZE.0, MAX_WINDOW_SIZE.0));
+        config.window_state.height =
+            Some(logical.height.clamp(MIN_WINDOW_SIZE.1, MAX_WINDOW_SIZE.1));
+    });
+}
+
+/// Main window geometry after a resize: size in logical pixels, position in physical pixels
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct AppliedSize {
+    pub width: f64,
+    pub height: f64,
+    pub x: i32,
+    pub y: i32,
+    /// Scale factor of the monitor the window is on, to convert between the two
+    pub scale_factor: f64,
+}
+
+/// Resize the main window while maintaining top-left anchor
+///
+/// `width` and `height` are logical pixels, so the window keeps the same apparent size
+/// across monitors with different scale factors. Returns the geometry read back from the
+/// window, which differs from the request when the window's size limits clamped it.
+#[tauri::command]
+pub fn resize_window(app: AppHandle, width: f64, height: f64) -> Result<AppliedSize, String> {
+    // A programmatic resize means any user drag-resize has finished, and its size is stale
+    app.state::<ResizeState>()
+        .dragging
+        .store(false, Ordering::Relaxed);
+    config::discard_deferred(&app, WINDOW_SIZE_SAVE_KEY);
+
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSWindow;
+        use cocoa::base::id;
+        use cocoa::foundation::{NSRect, NSSize};
+
+        unsafe {
+            let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+            let current_frame = ns_window.frame();
+
+            // Create new size
+            let new_size = NSSize::new(width, height);
+
+            // Calculate new origin (bottom-left) to keep top-left constant
+            let new_origin_y = current_frame.origin.y + current_frame.size.height - height;
+
+            let new_frame = NSRect::new(
+                cocoa::foundation::NSPoint::new(current_frame.origin.x, new_origin_y),
+                new_size,
+            );
+
+            ns_window.setFrame_display_animate_(new_frame, true, false);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        // Store current position to anchor the window
+        let current_pos = window.outer_position().ok();
+
+        let size = LogicalSize::new(width, height);
+        window.set_size(size).map_err(|e| e.to_string())?;
+
+        // Restore position to ensure top-left anchor
+        if let Some(pos) = current_pos {
+            let _ = window.set_position(pos);
+        }
+    }
+
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let size = window
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale);
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    Ok(AppliedSize {
+        width: size.width,
+        height: size.height,
+        x: position.x,
+        y: position.y,
+        scale_factor: scale,
+    })
+}
+
+/// Scale factor of the monitor the main window is currently on
+#[tauri::command]
+pub fn get_window_scale_factor(app: AppHandle) -> Result<f64, String> {
+    app.get_webview_window("main")
+        .ok_or("Main window not found")?
+        .scale_factor()
+        .map_err(|e| e.to_string())
+}
+
+/// Main window position in physical pixels, with the scale factor it now renders at
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct WindowMoved {
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f64,
+}
+
+/// Emit `window-moved` whenever the main window moves or lands on a monitor with another DPI
+pub fn track_window_moves(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let moved = match event {
+            WindowEvent::Moved(position) => WindowMoved {
+                x: position.x,
+                y: position.y,
+                scale_factor: tracked.scale_factor().unwrap_or(1.0),
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                let Ok(position) = tracked.outer_position() else {
+                    return;
+                };
+                WindowMoved {
+                    x: position.x,
+                    y: position.y,
+                    scale_factor: *scale_factor,
+                }
+            }
+            _ => return,
+        };
+        let _ = handle.emit("window-moved", moved);
+    });
+}
+
+/// Main window position in physical pixels and the monitor it's on
+#[derive(Debug, Serialize, Clone)]
+pub struct WindowPosition {
+    pub x: i32,
+    pub y: i32,
+    /// Name of the monitor the window is on
+    pub monitor_id: Option<String>,
+}
+
+/// Move the main window to an exact physical position, kept fully on a visible monitor
+///
+/// The position is saved to WindowState so it is restored on the next launch, and
+/// `window-moved` is emitted. Returns where the window actually landed after clamping.
+#[tauri::command]
+pub fn set_window_position(app: AppHandle, x: i32, y: i32) -> Result<WindowPosition, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let (x, y, monitor) = crate::monitors::clamp_to_monitor(&app, x, y, size.width, size.height)
+        .ok_or("No monitors found")?;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())?;
+
+    let mut config = config::load_config(&app);
+    config.window_state.x = Some(x);
+    config.window_state.y = Some(y);
+    config::save_config(&app, &config, "set_window_position")?;
+
+    let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+    let _ = app.emit("window-moved", WindowMoved { x, y, scale_factor });
+    Ok(WindowPosition {
+        x,
+        y,
+        monitor_id: monitor.name,
+    })
+}
+
+/// Current main window position in physical pixels
+#[tauri::command]
+pub fn get_window_position(app: AppHandle) -> Result<WindowPosition, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let monitor_id = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    Ok(WindowPosition {
+        x: position.x,
+        y: position.y,
+        monitor_id,
+    })
+}
+
+/// Size of the main window from tauri.conf.json, used when no full size was remembered
+const DEFAULT_WINDOW_SIZE: (f64, f64) = (650.0, 310.0);
+
+/// Collapse the overlay to a thin bar, or expand it back to the remembered full size
+#[tauri::command]
+pub fn set_compact_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    let mut config = config::load_config(&app);
+    let compact = &mut config.compact_mode;
 
-            unsafe {
-                let ns_window = window.ns_window().unwrap() as id;
-                let current_frame = ns_window.frame();
+    if enabled {
+        if !compact.enabled {
+            let scale = window.scale_factor().map_err(|e| e.to_string())?;
+            let size = window
+                .inner_size()
+                .map_err(|e| e.to_string())?
+                .to_logical::<f64>(scale);
+            compact.full_width = Some(size.width);
+            compact.full_height = Some(size.height);
+        }
+        resize_window(app.clone(), compact.width, compact.height)?;
+    } else {
+        resize_window(
+            app.clone(),
+            compact.full_width.unwrap_or(DEFAULT_WINDOW_SIZE.0),
+            compact.full_height.unwrap_or(DEFAULT_WINDOW_SIZE.1),
+        )?;
+    }
+    compact.enabled = enabled;
 
-                // Create new size
-                let new_size = NSSize::new(width, height);
+    config::save_config(&app, &config, "set_compact_mode")?;
+    let _ = app.emit("compact-mode-changed", enabled);
+    Ok(())
+}
+
+/// Put the main window back at its default size, centered on the primary monitor
+///
+/// Also forgets the saved WindowState and leaves compact mode. Emits `window-moved` and
+/// returns the applied geometry like resize_window.
+#[tauri::command]
+pub fn reset_window_geometry(app: AppHandle) -> Result<AppliedSize, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    resize_window(app.clone(), DEFAULT_WINDOW_SIZE.0, DEFAULT_WINDOW_SIZE.1)?;
+
+    let monitor = app
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .ok_or("No primary monitor found")?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let x = monitor.position().x + (monitor.size().width as i32 - size.width as i32).max(0) / 2;
+    let y = monitor.position().y + (monitor.size().height as i32 - size.height as i32).max(0) / 2;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())?;
+
+    let mut config = config::load_config(&app);
+    let was_compact = config.compact_mode.enabled;
+    config.window_state = WindowState::default();
+    config.compact_mode.enabled = false;
+    config.compact_mode.full_width = None;
+    config.compact_mode.full_height = None;
+    config::save_config(&app, &config, "reset_window_geometry")?;
+    if was_compact {
+        let _ = app.emit("compact-mode-changed", false);
+    }
+
+    let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+    let logical = window
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+    let _ = app.emit("window-moved", WindowMoved { x, y, scale_factor });
+    Ok(AppliedSize {
+        width: logical.width,
+        height: logical.height,
+        x,
+        y,
+        scale_factor,
+    })
+}
+
+/// Enable or disable content protection on all windows
+///
+/// Each window is checked afterwards where the OS lets us query it, so a request that
+/// didn't take effect is reported instead of silently ignored.
+#[tauri::command]
+pub fn set_content_protection(
+    app: AppHandle,
+    state: State<'_, ContentProtectionState>,
+    enabled: bool,
+) -> Result<(), String> {
+    apply_content_protection(&app, &state, enabled)
+}
 
-                // Calculate new origin (bottom-left) to keep top-left constant
-                let new_origin_y = current_frame.origin.y + current_frame.size.height - height;
+fn apply_content_protection(
+    app: &AppHandle,
+    state: &ContentProtectionState,
+    enabled: bool,
+) -> Result<(), String> {
+    state.enabled.store(enabled, Ordering::Relaxed);
+    state.overrides.lock().unwrap().clear();
 
-                let new_frame = NSRect::new(
-                    cocoa::foundation::NSPoint::new(current_frame.origin.x, new_origin_y),
-                    new_size,
+    let errors: Vec<String> = ["main", "settings", QUICK_NOTE_LABEL]
+        .into_iter()
+        .filter_map(|label| app.get_webview_window(label))
+        .filter_map(|window| {
+            protect_window(&window, enabled)
+                .err()
+                .map(|e| format!("{}: {}", window.label(), e))
+        })
+        .collect();
+
+    let result = if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(", "))
+    };
+    last_errors::track(app, Subsystem::ContentProtection, result)
+}
+
+/// Enable or disable content protection on a single window
+///
+/// The setting is remembered for the label, so a window that is closed and recreated (like
+/// the quick note) comes back with it.
+#[tauri::command]
+pub fn set_content_protection_for(
+    app: AppHandle,
+    state: State<'_, ContentProtectionState>,
+    label: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window {} not found", label))?;
+
+    state.overrides.lock().unwrap().insert(label, enabled);
+    last_errors::track(
+        &app,
+        Subsystem::ContentProtection,
+        protect_window(&window, enabled),
+    )
+}
+
+/// Keeps content protection off on our windows until dropped, then turns it back on
+///
+/// Restoring in Drop means a capture that fails or panics can't leave the windows exposed.
+pub struct ProtectionLift(Vec<WebviewWindow>);
+
+impl Drop for ProtectionLift {
+    fn drop(&mut self) {
+        for window in &self.0 {
+            if let Err(e) = protect_window(window, true) {
+                eprintln!(
+                    "Failed to restore content protection on {}: {}",
+                    window.label(),
+                    e
+                );
+                last_errors::record_error(
+                    window.app_handle(),
+                    Subsystem::ContentProtection,
+                    format!("{}: {}", window.label(), e),
                 );
+            }
+        }
+    }
+}
 
-                ns_window.setFrame_display_animate_(new_frame, true, false);
+/// Turn content protection off on every window that has it, for a single capture
+pub fn lift_content_protection(app: &AppHandle) -> ProtectionLift {
+    let state = app.state::<ContentProtectionState>();
+    let protected: Vec<WebviewWindow> = app
+        .webview_windows()
+        .into_values()
+        .filter(|window| state.is_enabled_for(window.label()))
+        .collect();
+    // A window that fails to unprotect still gets restored, in case it half-applied
+    for window in &protected {
+        if let Err(e) = protect_window(window, false) {
+            eprintln!(
+                "Failed to lift content protection on {}: {}",
+                window.label(),
+                e
+            );
+        }
+    }
+    ProtectionLift(protected)
+}
+
+/// Apply content protection to one window and verify it took effect
+///
+/// macOS maps this to NSWindowSharingNone. Windows uses WDA_EXCLUDEFROMCAPTURE, which only
+/// exists on Windows 10 2004 (build 19041) and later, so older versions get an error.
+fn protect_window(window: &WebviewWindow, enabled: bool) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    if enabled && windows_build_number() < 19041 {
+        return Err("Content protection requires Windows 10 version 2004 or later".to_string());
+    }
+
+    window
+        .set_content_protected(enabled)
+        .map_err(|e| e.to_string())?;
+
+    // Read the state back where the OS lets us, so a request that didn't stick is reported
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if is_window_protected(window)? != enabled {
+        return Err(format!(
+            "content protection is still {} after turning it {}",
+            if enabled { "off" } else { "on" },
+            if enabled { "on" } else { "off" }
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether the OS is actually excluding the window from captures right now
+fn is_window_protected(window: &WebviewWindow) -> Result<bool, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::id;
+        use objc::{msg_send, sel, sel_impl};
+
+        // NSWindowSharingNone
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        let sharing_type: u64 = unsafe { msg_send![ns_window, sharingType] };
+        Ok(sharing_type == 0)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{GetWindowDisplayAffinity, WDA_NONE};
+
+        // WDA_MONITOR blacks the window out instead of hiding it, but still keeps it private
+        let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0);
+        let mut affinity = 0u32;
+        unsafe { GetWindowDisplayAffinity(hwnd, &mut affinity) }.map_err(|e| e.to_string())?;
+        Ok(affinity != WDA_NONE.0)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = window;
+        Err("Content protection can't be verified on this platform".to_string())
+    }
+}
+
+/// The OS-reported protection of one window
+#[derive(Debug, Serialize, Clone)]
+pub struct WindowProtection {
+    pub label: String,
+    /// None when the state couldn't be read, with the reason in `error`
+    pub protected: Option<bool>,
+    pub error: Option<String>,
+}
+
+/// Whether content protection is really in force, as opposed to merely requested
+#[derive(Debug, Serialize, Clone)]
+pub struct ContentProtectionStatus {
+    /// True only if every open window was verified as excluded from capture
+    pub effective: bool,
+    pub windows: Vec<WindowProtection>,
+}
+
+/// Ask the OS whether each open window is excluded from screen capture
+///
+/// Reads sharingType on macOS and the display affinity on Windows. Elsewhere the state
+/// can't be read, so protection is never reported as effective.
+#[tauri::command]
+pub fn is_content_protection_effective(app: AppHandle) -> ContentProtectionStatus {
+    let windows: Vec<WindowProtection> = ["main", "settings", QUICK_NOTE_LABEL]
+        .into_iter()
+        .filter_map(|label| app.get_webview_window(label))
+        .map(|window| {
+            let result = is_window_protected(&window);
+            WindowProtection {
+                label: window.label().to_string(),
+                protected: result.as_ref().ok().copied(),
+                error: result.err(),
             }
+        })
+        .collect();
+
+    ContentProtectionStatus {
+        effective: !windows.is_empty() && windows.iter().all(|w| w.protected == Some(true)),
+        windows,
+    }
+}
+
+/// The Windows build number, read via RtlGetVersion since GetVersionEx lies without a manifest
+#[cfg(target_os = "windows")]
+fn windows_build_number() -> u32 {
+    use windows::Wdk::System::SystemServices::RtlGetVersion;
+    use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+    let mut info = OSVERSIONINFOW {
+        dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+    let status = unsafe { RtlGetVersion(&mut info) };
+    if status.is_ok() {
+        info.dwBuildNumber
+    } else {
+        0
+    }
+}
+
+/// Partial window settings; only the fields that are set get applied
+#[derive(Debug, Deserialize, Default)]
+pub struct WindowSettings {
+    pub opacity: Option<f64>,
+    pub always_on_top: Option<bool>,
+    pub click_through: Option<bool>,
+    pub content_protection: Option<bool>,
+}
+
+/// Apply several window settings in one call and persist them together
+///
+/// Values are validated before anything is applied, and if applying one fails the ones
+/// already applied are put back, so either every setting takes effect or none does.
+/// Returns the resulting effective settings; the ones not in `settings` are read from the
+/// window where possible, since the standalone setters don't save to config.
+#[tauri::command]
+pub fn apply_window_settings(
+    app: AppHandle,
+    state: State<'_, ContentProtectionState>,
+    settings: WindowSettings,
+) -> Result<WindowConfig, String> {
+    if let Some(opacity) = settings.opacity {
+        if !(MIN_OPACITY..=1.0).contains(&opacity) {
+            return Err(format!(
+                "Opacity must be between {} and 1.0, got {}",
+                MIN_OPACITY, opacity
+            ));
         }
+    }
 
-        #[cfg(not(target_os = "macos"))]
-        {
-            use tauri::LogicalSize;
-            // Store current position to anchor the window
-            let current_pos = window.outer_position().ok();
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    let mut config = config::load_config(&app);
+    read_live_settings(&window, &mut config.window);
+    config.window.content_protection = state.is_enabled();
+    let previous = config.window.clone();
+    let previous_overrides = state.overrides.lock().unwrap().clone();
+    let previous_protection = state.is_enabled();
+
+    if let Err(e) = apply_to_window(&app, &window, &state, &settings, &mut config.window) {
+        // Best effort: a setting that failed to apply may fail to revert too
+        if settings.opacity.is_some() {
+            let _ = set_window_opacity(&window, previous.opacity);
+        }
+        if settings.always_on_top.is_some() {
+            let _ = window.set_always_on_top(previous.always_on_top);
+        }
+        if settings.click_through.is_some() {
+            let _ = window.set_ignore_cursor_events(previous.click_through);
+        }
+        if settings.content_protection.is_some() {
+            state.enabled.store(previous_protection, Ordering::Relaxed);
+            *state.overrides.lock().unwrap() = previous_overrides;
+            for window in app.webview_windows().into_values() {
+                let _ = protect_window(&window, state.is_enabled_for(window.label()));
+            }
+        }
+        return Err(e);
+    }
 
-            let size = LogicalSize::new(width, height);
-            let _ = window.set_size(size);
+    config::save_config(&app, &config, "apply_window_settings")?;
+    Ok(config.window)
+}
 
-            // Restore position to ensure top-left anchor
-            if let Some(pos) = current_pos {
-                let _ = window.set_position(pos);
+/// Apply each setting that is set in turn, recording it in `applied`; stops at the first error
+fn apply_to_window(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    state: &ContentProtectionState,
+    settings: &WindowSettings,
+    applied: &mut WindowConfig,
+) -> Result<(), String> {
+    if let Some(opacity) = settings.opacity {
+        set_window_opacity(window, opacity)?;
+        applied.opacity = opacity;
+    }
+    if let Some(always_on_top) = settings.always_on_top {
+        window
+            .set_always_on_top(always_on_top)
+            .map_err(|e| e.to_string())?;
+        applied.always_on_top = always_on_top;
+    }
+    if let Some(click_through) = settings.click_through {
+        window
+            .set_ignore_cursor_events(click_through)
+            .map_err(|e| e.to_string())?;
+        applied.click_through = click_through;
+    }
+    if let Some(enabled) = settings.content_protection {
+        apply_content_protection(app, state, enabled)?;
+        applied.content_protection = enabled;
+    }
+    Ok(())
+}
+
+/// The main window's live settings, as written to config by sync_window_state_to_config
+#[derive(Debug, Serialize, Clone)]
+pub struct SyncedWindowState {
+    pub window: WindowConfig,
+    pub window_state: WindowState,
+}
+
+/// Save the main window's current geometry, opacity, always-on-top and click-through
+/// state as the preferred settings
+///
+/// Nothing is persisted automatically after a manual move or drag; this snapshots the
+/// arrangement on request. Settings the platform can't read back keep their saved value.
+#[tauri::command]
+pub fn sync_window_state_to_config(app: AppHandle) -> Result<SyncedWindowState, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let size = window
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale);
+
+    let mut config = config::load_config(&app);
+    config.window_state = WindowState {
+        x: Some(position.x),
+        y: Some(position.y),
+        width: Some(size.width.clamp(MIN_WINDOW_SIZE.0, MAX_WINDOW_SIZE.0)),
+        height: Some(size.height.clamp(MIN_WINDOW_SIZE.1, MAX_WINDOW_SIZE.1)),
+    };
+    read_live_settings(&window, &mut config.window);
+
+    config::save_config(&app, &config, "sync_window_state_to_config")?;
+    Ok(SyncedWindowState {
+        window: config.window,
+        window_state: config.window_state,
+    })
+}
+
+/// Overwrite `settings` with the window's current opacity, always-on-top and click-through
+/// state, keeping the saved value for any the platform can't read back
+fn read_live_settings(window: &WebviewWindow, settings: &mut WindowConfig) {
+    if let Ok(always_on_top) = window.is_always_on_top() {
+        settings.always_on_top = always_on_top;
+    }
+    if let Some(opacity) = window_opacity(window) {
+        settings.opacity = opacity.clamp(MIN_OPACITY, 1.0);
+    }
+    if let Some(click_through) = window_click_through(window) {
+        settings.click_through = click_through;
+    }
+}
+
+/// Read the whole-window alpha back from the native window handle
+fn window_opacity(window: &WebviewWindow) -> Option<f64> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSWindow;
+        use cocoa::base::id;
+
+        let ns_window = window.ns_window().ok()? as id;
+        Some(unsafe { ns_window.alphaValue() })
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetLayeredWindowAttributes, GetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+        };
+
+        let hwnd = HWND(window.hwnd().ok()?.0);
+        unsafe {
+            if GetWindowLongPtrW(hwnd, GWL_EXSTYLE) & WS_EX_LAYERED.0 as isize == 0 {
+                return Some(1.0);
             }
+            let mut alpha = 255u8;
+            let mut flags = Default::default();
+            GetLayeredWindowAttributes(hwnd, None, Some(&mut alpha), Some(&mut flags)).ok()?;
+            Some(if flags & LWA_ALPHA == LWA_ALPHA {
+                alpha as f64 / 255.0
+            } else {
+                1.0
+            })
         }
     }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = window;
+        None
+    }
 }
 
-/// Enable or disable content protection on all windows
+/// Whether the native window currently lets clicks pass through
+fn window_click_through(window: &WebviewWindow) -> Option<bool> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSWindow;
+        use cocoa::base::{id, YES};
+
+        let ns_window = window.ns_window().ok()? as id;
+        Some(unsafe { ns_window.ignoresMouseEvents() } == YES)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, GWL_EXSTYLE, WS_EX_TRANSPARENT,
+        };
+
+        let hwnd = HWND(window.hwnd().ok()?.0);
+        let ex_style = unsafe { GetWindowLongPtrW(hwnd, GWL_EXSTYLE) };
+        Some(ex_style & WS_EX_TRANSPARENT.0 as isize != 0)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = window;
+        None
+    }
+}
+
+/// Reload the main webview's content without restarting the process
+///
+/// Once the new page has loaded, every persisted native window setting is applied again
+/// and `window-reloaded` is emitted, so the overlay looks the same as before the reload.
 #[tauri::command]
-pub fn set_content_protection(app: AppHandle, enabled: bool) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.set_content_protected(enabled);
+pub fn reload_window(app: AppHandle, state: State<'_, ReloadState>) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    state.0.store(true, Ordering::SeqCst);
+    window.reload().map_err(|e| {
+        state.0.store(false, Ordering::SeqCst);
+        e.to_string()
+    })
+}
+
+/// Page-load hook for reload_window: reapply native settings once the main page finishes
+pub fn handle_page_load(webview: &Webview, payload: &PageLoadPayload<'_>) {
+    if webview.label() != "main" || payload.event() != PageLoadEvent::Finished {
+        return;
+    }
+    let app = webview.app_handle();
+    if !app.state::<ReloadState>().0.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    reapply_native_settings(app);
+    let _ = app.emit("window-reloaded", ());
+}
+
+/// Apply every persisted native window setting to the main window again
+fn reapply_native_settings(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let config = config::load_config(app);
+
+    let mut errors = Vec::new();
+    if config.window.opacity < 1.0 {
+        if let Err(e) = set_window_opacity(&window, config.window.opacity) {
+            errors.push(format!("opacity: {}", e));
+        }
+    }
+    if let Err(e) = window.set_always_on_top(config.window.always_on_top) {
+        errors.push(format!("always on top: {}", e));
+    }
+    if let Err(e) = window.set_ignore_cursor_events(config.window.click_through) {
+        errors.push(format!("click-through: {}", e));
+    }
+    // From the live state rather than config: set_content_protection doesn't save its
+    // choice, and the per-window overrides have to survive the reload too
+    let protection = app.state::<ContentProtectionState>();
+    for window in app.webview_windows().into_values() {
+        if let Err(e) = protect_window(&window, protection.is_enabled_for(window.label())) {
+            errors.push(format!("content protection on {}: {}", window.label(), e));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Err(e) = apply_overlay_level(
+            app,
+            config.overlay_above_fullscreen,
+            config.window.always_on_top,
+        ) {
+            errors.push(format!("overlay level: {}", e));
+        }
+        if let Err(e) = apply_window_shadow(app, config.window_shadow) {
+            errors.push(format!("shadow: {}", e));
+        }
+        if let Err(e) = apply_corner_radius(app, config.corner_radius) {
+            errors.push(format!("corner radius: {}", e));
+        }
+        if config.window_vibrancy.is_some() {
+            if let Err(e) = apply_window_vibrancy(app, config.window_vibrancy) {
+                errors.push(format!("vibrancy: {}", e));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        eprintln!(
+            "Failed to reapply window settings after reload: {}",
+            errors.join(", ")
+        );
+    }
+}
+
+/// Set the whole-window alpha using the native window handle
+fn set_window_opacity(window: &WebviewWindow, opacity: f64) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSWindow;
+        use cocoa::base::id;
+
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        unsafe {
+            ns_window.setAlphaValue_(opacity);
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::{COLORREF, HWND};
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+            LWA_ALPHA, WS_EX_LAYERED,
+        };
+
+        let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0);
+        unsafe {
+            let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED.0 as isize);
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), (opacity * 255.0) as u8, LWA_ALPHA)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = (window, opacity);
+        Err("Window opacity is not supported on this platform".to_string())
     }
-    if let Some(window) = app.get_webview_window("settings") {
-        let _ = window.set_content_protected(enabled);
+}
+
+/// Raise the overlay above other apps' native fullscreen windows (macOS only)
+///
+/// Only the window level and collection behavior change; the window is never activated,
+/// so the accessory activation policy keeps working. Sandboxed builds may need extra
+/// entitlements before windows at screen-saver level are allowed over fullscreen spaces.
+#[tauri::command]
+pub fn set_overlay_level(app: AppHandle, above_fullscreen: bool) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    apply_overlay_level(&app, above_fullscreen, config.window.always_on_top)?;
+
+    config.overlay_above_fullscreen = above_fullscreen;
+    config::save_config(&app, &config, "set_overlay_level")
+}
+
+/// Apply the persisted overlay level to the main window
+///
+/// Below fullscreen the window goes back to the level `always_on_top` calls for: floating
+/// when it's set, normal otherwise.
+pub fn apply_overlay_level(
+    app: &AppHandle,
+    above_fullscreen: bool,
+    always_on_top: bool,
+) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSWindow, NSWindowCollectionBehavior};
+        use cocoa::base::id;
+
+        // NSScreenSaverWindowLevel / NSFloatingWindowLevel / NSNormalWindowLevel
+        const SCREEN_SAVER_LEVEL: i64 = 1000;
+        const FLOATING_LEVEL: i64 = 3;
+        const NORMAL_LEVEL: i64 = 0;
+
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+        unsafe {
+            let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+            let mut collection_behavior = ns_window.collectionBehavior();
+            if above_fullscreen {
+                collection_behavior |=
+                    NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary;
+                ns_window.setLevel_(SCREEN_SAVER_LEVEL);
+            } else {
+                collection_behavior &=
+                    !NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary;
+                ns_window.setLevel_(if always_on_top {
+                    FLOATING_LEVEL
+                } else {
+                    NORMAL_LEVEL
+                });
+            }
+            ns_window.setCollectionBehavior_(collection_behavior);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, above_fullscreen, always_on_top);
+        Ok(())
+    }
+}
+
+/// Change whether Lens appears in the Dock and Cmd+Tab (macOS only)
+///
+/// Takes effect immediately: switching to Regular makes the Dock icon appear right away.
+#[tauri::command]
+pub fn set_activation_policy(app: AppHandle, policy: ActivationPolicy) -> Result<(), String> {
+    apply_activation_policy(policy);
+
+    let mut config = config::load_config(&app);
+    config.macos_activation_policy = policy;
+    config::save_config(&app, &config, "set_activation_policy")
+}
+
+/// Apply an activation policy to the running application
+pub fn apply_activation_policy(policy: ActivationPolicy) {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSApplication, NSApplicationActivationPolicy};
+        use cocoa::base::nil;
+
+        let ns_policy = match policy {
+            ActivationPolicy::Accessory => {
+                NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory
+            }
+            ActivationPolicy::Regular => {
+                NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular
+            }
+        };
+        unsafe {
+            let ns_app = NSApplication::sharedApplication(nil);
+            ns_app.setActivationPolicy_(ns_policy);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = policy;
+    }
+}
+
+/// Launch to the tray without showing the overlay; takes effect on the next start
+#[tauri::command]
+pub fn set_start_hidden(app: AppHandle, start_hidden: bool) -> Result<(), String> {
+    let mut config = config::load_config(&app);
+    config.start_hidden = start_hidden;
+    config::save_config(&app, &config, "set_start_hidden")
+}
+
+/// Turn the native drop shadow behind the main window on or off (macOS only)
+#[tauri::command]
+pub fn set_window_shadow(app: AppHandle, enabled: bool) -> Result<(), String> {
+    apply_window_shadow(&app, enabled)?;
+
+    let mut config = config::load_config(&app);
+    config.window_shadow = enabled;
+    config::save_config(&app, &config, "set_window_shadow")
+}
+
+/// Apply the persisted window shadow to the main window
+pub fn apply_window_shadow(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSWindow;
+        use cocoa::base::{id, NO, YES};
+
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        unsafe {
+            ns_window.setHasShadow_(if enabled { YES } else { NO });
+            ns_window.invalidateShadow();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, enabled);
+        Err("Window shadow is only supported on macOS".to_string())
+    }
+}
+
+/// Round the corners of the main window's content (macOS only); 0 restores square corners
+#[tauri::command]
+pub fn set_corner_radius(app: AppHandle, radius: f64) -> Result<(), String> {
+    if !radius.is_finite() || radius < 0.0 {
+        return Err("Corner radius must be a non-negative number".to_string());
+    }
+    apply_corner_radius(&app, radius)?;
+
+    let mut config = config::load_config(&app);
+    config.corner_radius = radius;
+    config::save_config(&app, &config, "set_corner_radius")
+}
+
+/// Apply the persisted corner radius to the main window
+///
+/// The radius is set on the content view's layer, not the window frame, so masksToBounds
+/// only clips the web content. The shadow is drawn by the window server outside that layer
+/// and is recomputed from the now-rounded alpha, so it follows the corners instead of
+/// being cut off square.
+pub fn apply_corner_radius(app: &AppHandle, radius: f64) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSView, NSWindow};
+        use cocoa::base::{id, nil, NO, YES};
+        use objc::{msg_send, sel, sel_impl};
+
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        unsafe {
+            let content_view = ns_window.contentView();
+            content_view.setWantsLayer(YES);
+            let layer = content_view.layer();
+            if layer == nil {
+                return Err("Window content is not layer-backed".to_string());
+            }
+            let _: () = msg_send![layer, setCornerRadius: radius];
+            let _: () = msg_send![layer, setMasksToBounds: if radius > 0.0 { YES } else { NO }];
+            ns_window.invalidateShadow();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, radius);
+        Err("Corner radius is only supported on macOS".to_string())
+    }
+}
+
+/// Put a frosted-glass effect behind the main window's web content (macOS only)
+///
+/// None removes it again. The web content must leave its background transparent for the
+/// effect to show through.
+#[tauri::command]
+pub fn set_window_vibrancy(
+    app: AppHandle,
+    material: Option<VibrancyMaterial>,
+) -> Result<(), String> {
+    apply_window_vibrancy(&app, material)?;
+
+    let mut config = config::load_config(&app);
+    config.window_vibrancy = material;
+    config::save_config(&app, &config, "set_window_vibrancy")
+}
+
+/// Apply the persisted vibrancy material to the main window
+///
+/// The effect view is tagged with an identifier so a later call can find it to change its
+/// material or remove it, instead of stacking another view underneath.
+pub fn apply_window_vibrancy(
+    app: &AppHandle,
+    material: Option<VibrancyMaterial>,
+) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::{NSView, NSWindow};
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::NSString;
+        use objc::{class, msg_send, sel, sel_impl};
+
+        const EFFECT_VIEW_ID: &str = "LensVibrancyView";
+        // NSVisualEffectBlendingModeBehindWindow, NSVisualEffectStateActive
+        const BLENDING_BEHIND_WINDOW: i64 = 0;
+        const STATE_ACTIVE: i64 = 1;
+        // NSViewWidthSizable | NSViewHeightSizable
+        const RESIZE_WITH_SUPERVIEW: u64 = 2 | 16;
+        // NSWindowBelow
+        const BELOW: i64 = -1;
+
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        unsafe {
+            let content_view = ns_window.contentView();
+            let identifier = NSString::alloc(nil).init_str(EFFECT_VIEW_ID);
+
+            let subviews: id = msg_send![content_view, subviews];
+            let count: usize = msg_send![subviews, count];
+            let mut effect_view: id = nil;
+            for i in 0..count {
+                let view: id = msg_send![subviews, objectAtIndex: i];
+                let view_id: id = msg_send![view, identifier];
+                if view_id != nil {
+                    let same: bool = msg_send![view_id, isEqualToString: identifier];
+                    if same {
+                        effect_view = view;
+                        break;
+                    }
+                }
+            }
+
+            let Some(material) = material else {
+                if effect_view != nil {
+                    let _: () = msg_send![effect_view, removeFromSuperview];
+                }
+                return Ok(());
+            };
+
+            if effect_view == nil {
+                effect_view = msg_send![class!(NSVisualEffectView), alloc];
+                effect_view = msg_send![effect_view, initWithFrame: NSView::bounds(content_view)];
+                let _: () = msg_send![effect_view, setIdentifier: identifier];
+                let _: () = msg_send![effect_view, setAutoresizingMask: RESIZE_WITH_SUPERVIEW];
+                let _: () = msg_send![effect_view, setBlendingMode: BLENDING_BEHIND_WINDOW];
+                let _: () = msg_send![effect_view, setState: STATE_ACTIVE];
+                let _: () = msg_send![content_view,
+                    addSubview: effect_view
+                    positioned: BELOW
+                    relativeTo: nil];
+                // The superview holds it now
+                let _: () = msg_send![effect_view, release];
+            }
+            let _: () = msg_send![effect_view, setMaterial: ns_material(material)];
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, material);
+        Err("Window vibrancy is only supported on macOS".to_string())
+    }
+}
+
+/// NSVisualEffectMaterial raw value for a material
+#[cfg(target_os = "macos")]
+fn ns_material(material: VibrancyMaterial) -> i64 {
+    match material {
+        VibrancyMaterial::Titlebar => 3,
+        VibrancyMaterial::Selection => 4,
+        VibrancyMaterial::Menu => 5,
+        VibrancyMaterial::Popover => 6,
+        VibrancyMaterial::Sidebar => 7,
+        VibrancyMaterial::HeaderView => 10,
+        VibrancyMaterial::Sheet => 11,
+        VibrancyMaterial::WindowBackground => 12,
+        VibrancyMaterial::HudWindow => 13,
+        VibrancyMaterial::FullScreenUi => 15,
+        VibrancyMaterial::Tooltip => 17,
+        VibrancyMaterial::ContentBackground => 18,
+        VibrancyMaterial::UnderWindowBackground => 21,
+        VibrancyMaterial::UnderPageBackground => 22,
     }
 }
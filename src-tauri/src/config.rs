@@ -1,59 +1,1034 @@
-use serde::{Deserialize, Serialize};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    ArrayValidation, InstanceType, Metadata, Schema, SchemaObject, StringValidation,
+    SubschemaValidation,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::capture::{CaptureFormat, PngCompression};
+use crate::last_errors::{self, Subsystem};
+
+/// Shortcut combos per action; an action fires if any of its combos is pressed
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct ShortcutsConfig {
-    pub toggle: String,
-    pub ask: String,
-    pub screen_share: String,
+    #[serde(deserialize_with = "one_or_many")]
+    #[schemars(schema_with = "combos_schema")]
+    pub toggle: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    #[schemars(schema_with = "combos_schema")]
+    pub ask: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    #[schemars(schema_with = "combos_schema")]
+    pub screen_share: Vec<String>,
+    #[serde(
+        default = "default_quick_note_shortcut",
+        deserialize_with = "one_or_many"
+    )]
+    #[schemars(schema_with = "combos_schema")]
+    pub quick_note: Vec<String>,
 }
 
 impl Default for ShortcutsConfig {
     fn default() -> Self {
         Self {
-            toggle: "CommandOrControl+Backslash".to_string(),
-            ask: "CommandOrControl+Enter".to_string(),
-            screen_share: "CommandOrControl+S".to_string(),
+            toggle: vec!["CommandOrControl+Backslash".to_string()],
+            ask: vec!["CommandOrControl+Enter".to_string()],
+            screen_share: vec!["CommandOrControl+S".to_string()],
+            quick_note: default_quick_note_shortcut(),
+        }
+    }
+}
+
+impl ShortcutsConfig {
+    /// Each action paired with its shortcut combos
+    pub fn bindings(&self) -> [(&'static str, &[String]); 4] {
+        [
+            ("toggle", &self.toggle),
+            ("ask", &self.ask),
+            ("screen_share", &self.screen_share),
+            ("quick_note", &self.quick_note),
+        ]
+    }
+
+    /// The combos bound to an action, or None for an unknown action
+    pub fn action_mut(&mut self, action: &str) -> Option<&mut Vec<String>> {
+        match action {
+            "toggle" => Some(&mut self.toggle),
+            "ask" => Some(&mut self.ask),
+            "screen_share" => Some(&mut self.screen_share),
+            "quick_note" => Some(&mut self.quick_note),
+            _ => None,
+        }
+    }
+}
+
+/// Accept a single combo or a list, so configs from before multi-binding still load
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(combo) => vec![combo],
+        OneOrMany::Many(combos) => combos,
+    })
+}
+
+/// Schema for a shortcut field: one combo or a list of them, as one_or_many accepts
+fn combos_schema(_: &mut SchemaGenerator) -> Schema {
+    let combo: Schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(r"^([A-Za-z]+\+)*([A-Za-z0-9]+|[-=,./;'`\[\]\\])$".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into();
+    let combos: Schema = SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(combo.clone().into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into();
+
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(
+                "Modifiers and a key joined by '+', e.g. \"CommandOrControl+Shift+N\" or \
+                 \"Control+,\". Modifiers: Shift, Control, Alt, Super, CommandOrControl."
+                    .to_string(),
+            ),
+            ..Default::default()
+        })),
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![combo, combos]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn default_quick_note_shortcut() -> Vec<String> {
+    vec!["CommandOrControl+Shift+N".to_string()]
+}
+
+/// Persisted geometry for the quick note window, independent of the main overlay
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct QuickNoteConfig {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for QuickNoteConfig {
+    fn default() -> Self {
+        Self {
+            x: None,
+            y: None,
+            width: 280.0,
+            height: 220.0,
+        }
+    }
+}
+
+/// Native window appearance and behavior, applied together by apply_window_settings
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub opacity: f64,
+    pub always_on_top: bool,
+    pub click_through: bool,
+    pub content_protection: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            opacity: 1.0,
+            always_on_top: true,
+            click_through: false,
+            content_protection: false,
+        }
+    }
+}
+
+/// Last user-chosen geometry of the main window (position in physical, size in logical pixels)
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+#[serde(default)]
+pub struct WindowState {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+/// Compact "lens bar" mode, where the overlay collapses to a thin strip
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct CompactModeConfig {
+    pub enabled: bool,
+    /// Size of the strip, in logical pixels
+    pub width: f64,
+    pub height: f64,
+    /// Full size to restore when leaving compact mode
+    pub full_width: Option<f64>,
+    pub full_height: Option<f64>,
+}
+
+impl Default for CompactModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            width: 650.0,
+            height: 48.0,
+            full_width: None,
+            full_height: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// Whether Lens shows in the Dock and Cmd+Tab on macOS
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum ActivationPolicy {
+    /// No Dock icon or Cmd+Tab entry, but the overlay can still take focus
+    #[default]
+    Accessory,
+    Regular,
+}
+
+/// NSVisualEffectView material for the frosted overlay background (macOS only)
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+pub enum VibrancyMaterial {
+    Titlebar,
+    Selection,
+    Menu,
+    Popover,
+    Sidebar,
+    HeaderView,
+    Sheet,
+    WindowBackground,
+    HudWindow,
+    FullScreenUi,
+    Tooltip,
+    ContentBackground,
+    UnderWindowBackground,
+    UnderPageBackground,
+}
+
+/// How screens are captured on macOS
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum MacosCaptureBackend {
+    /// CoreGraphics before macOS 14, the screencapture command from 14 (where CG is deprecated)
+    #[default]
+    Auto,
+    ScreenCaptureKit,
+    CoreGraphics,
+    ScreencaptureCmd,
+}
+
+/// What to do with a capture larger than `max_capture_pixels`
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum OversizeCapture {
+    /// Scale the image down to fit the limit
+    #[default]
+    Downscale,
+    /// Fail the capture with an error
+    Refuse,
+}
+
+/// Clockwise rotation applied to full-screen captures before encoding
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum CaptureRotation {
+    /// Leave captures as the OS returns them, which already match the display's orientation.
+    /// Older configs may say "Auto", which is read as this.
+    #[default]
+    #[serde(alias = "Auto")]
+    None,
+    #[serde(rename = "90")]
+    Rotate90,
+    #[serde(rename = "180")]
+    Rotate180,
+    #[serde(rename = "270")]
+    Rotate270,
+}
+
+/// Which releases the updater offers
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Prereleases as well as stable releases
+    Beta,
+}
+
+/// What a capture shortcut captures
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+pub enum CaptureTarget {
+    #[default]
+    FullScreen,
+    /// The monitor holding the focused window
+    ActiveMonitor,
+    /// Let the user drag out a region first
+    Region,
+}
+
+/// An extra modifier that sends the screen_share shortcut to another capture target
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct ModifierTarget {
+    /// Modifier added to each screen_share combo, e.g. "Shift"
+    pub modifier: String,
+    pub target: CaptureTarget,
+}
+
+/// A named set of capture settings applied together by capture_with_profile
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct CaptureProfile {
+    pub name: String,
+    pub format: CaptureFormat,
+    pub compression: PngCompression,
+    /// Scale the capture down so neither side exceeds this many pixels
+    pub max_dimension: Option<u32>,
+    pub grayscale: bool,
+}
+
+impl Default for CaptureProfile {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            format: CaptureFormat::default(),
+            compression: PngCompression::default(),
+            max_dimension: None,
+            grayscale: false,
+        }
+    }
+}
+
+/// Capture limits and behavior
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Largest capture, in pixels, before `oversize_capture` kicks in (guards huge displays)
+    pub max_capture_pixels: u64,
+    pub oversize_capture: OversizeCapture,
+    /// Variants of the screen_share shortcut, e.g. Shift for a region capture
+    pub modifier_targets: Vec<ModifierTarget>,
+    /// Named presets for capture_with_profile
+    pub profiles: Vec<CaptureProfile>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            max_capture_pixels: 20_000_000,
+            oversize_capture: OversizeCapture::default(),
+            modifier_targets: Vec::new(),
+            profiles: vec![CaptureProfile::default()],
+        }
+    }
+}
+
+/// Endpoint capture_and_upload may send captures to (off until a URL is set)
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct UploadConfig {
+    /// http(s) URL captures are POSTed to; None disables uploading
+    pub url: Option<String>,
+    pub timeout_ms: u64,
+    /// Largest encoded capture that will be sent
+    pub max_bytes: u64,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_ms: 30_000,
+            max_bytes: 20 * 1024 * 1024,
+        }
+    }
+}
+
+/// Local HTTP server that lets other tools drive Lens (off by default)
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct AutomationConfig {
+    pub enabled: bool,
+    /// Port on 127.0.0.1 to listen on
+    pub port: u16,
+    /// Sent by clients as `Authorization: Bearer <token>`; the server won't start without one
+    pub token: Option<String>,
+}
+
+impl Default for AutomationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47615,
+            token: None,
+        }
+    }
+}
+
+/// Screen corner or edge that raises the overlay when the cursor rests against it
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+pub enum HotCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    TopEdge,
+    BottomEdge,
+    LeftEdge,
+    RightEdge,
+}
+
+/// Show the overlay when the cursor is pushed into a screen corner or edge
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct HotCornerConfig {
+    /// None disables the hot corner
+    pub corner: Option<HotCorner>,
+    /// How long the cursor must stay in the corner before the overlay appears
+    pub dwell_ms: u64,
+}
+
+impl Default for HotCornerConfig {
+    fn default() -> Self {
+        Self {
+            corner: None,
+            dwell_ms: 500,
+        }
+    }
+}
+
+/// A named, switchable set of shortcuts
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct ShortcutProfile {
+    pub id: String,
+    pub name: String,
+    pub shortcuts: ShortcutsConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+#[serde(default)]
 pub struct AppConfig {
     pub shortcuts: ShortcutsConfig,
+    pub quick_note: QuickNoteConfig,
+    pub overlay_above_fullscreen: bool,
+    pub window: WindowConfig,
+    pub window_state: WindowState,
+    pub profiles: Vec<ShortcutProfile>,
+    pub active_profile: Option<String>,
+    pub macos_activation_policy: ActivationPolicy,
+    pub macos_capture_backend: MacosCaptureBackend,
+    /// Never let a capture touch disk, at the cost of the file-based capture paths
+    ///
+    /// Off by default: on macOS 14+ the only in-memory backend is the deprecated
+    /// CoreGraphics one, which Auto avoids there. It can default on once ScreenCaptureKit
+    /// is available.
+    pub capture_memory_only: bool,
+    pub compact_mode: CompactModeConfig,
+    /// Re-decode each capture and reject degenerate images
+    pub verify_captures: bool,
+    /// App-wide mute for notification and feedback sounds
+    pub muted: bool,
+    /// Hide the overlay after this long without focus, captures or commands; None disables it
+    pub auto_hide_after_ms: Option<u64>,
+    /// Hide the overlay once it has been unfocused this long; None disables it
+    pub hide_on_blur_delay_ms: Option<u64>,
+    pub capture: CaptureConfig,
+    /// Fix for captures of rotated displays coming out sideways
+    pub capture_rotation: CaptureRotation,
+    /// Native drop shadow behind the main window (macOS only)
+    pub window_shadow: bool,
+    /// Corner radius of the main window's content in points; 0 keeps square corners (macOS only)
+    pub corner_radius: f64,
+    /// Frosted-glass material behind the web content; None disables it (macOS only)
+    pub window_vibrancy: Option<VibrancyMaterial>,
+    /// Hold-to-peek shortcut, registered even while the window is hidden; None disables it
+    pub peek_shortcut: Option<String>,
+    /// Launch straight to the tray without showing the overlay
+    pub start_hidden: bool,
+    pub automation: AutomationConfig,
+    pub update_channel: UpdateChannel,
+    /// Write config.json on one line instead of pretty-printed, for a smaller synced file
+    pub compact_json: bool,
+    pub hot_corner: HotCornerConfig,
+    /// Keep the ask shortcut registered while hidden and show the overlay when it's pressed
+    pub ask_shows_window: bool,
+    /// Name of the monitor capture_screen captures; falls back to the primary when absent
+    pub default_capture_monitor: Option<String>,
+    /// Let a held shortcut fire again on every OS key repeat instead of once per press
+    pub allow_key_repeat: bool,
+    pub upload: UploadConfig,
+    /// Image file shown as the tray icon instead of the app icon
+    pub tray_icon_path: Option<String>,
+    /// Milliseconds a shortcut action stays blocked after it runs, by action; absent is none
+    pub action_cooldowns_ms: HashMap<String, u64>,
+}
+
+impl AppConfig {
+    /// Bring configs written by older versions up to date, returning whether anything changed
+    fn migrate(&mut self) -> bool {
+        let mut migrated = false;
+
+        // Configs from before profiles existed get their shortcuts as the default profile
+        if self.profiles.is_empty() {
+            self.profiles.push(ShortcutProfile {
+                id: "default".to_string(),
+                name: "Default".to_string(),
+                shortcuts: self.shortcuts.clone(),
+            });
+            self.active_profile = Some("default".to_string());
+            migrated = true;
+        }
+
+        migrated
+    }
+
+    /// The macOS capture backend to use, with capture_memory_only taken into account
+    ///
+    /// The screencapture command only writes to a file, so memory-only capture pins the
+    /// in-memory CoreGraphics backend, deprecated from macOS 14 but still working there.
+    pub fn capture_backend(&self) -> MacosCaptureBackend {
+        if self.capture_memory_only {
+            MacosCaptureBackend::CoreGraphics
+        } else {
+            self.macos_capture_backend
+        }
+    }
+
+    /// Copy the live shortcuts into the active profile after they change
+    pub fn sync_active_profile(&mut self) {
+        let shortcuts = self.shortcuts.clone();
+        if let Some(profile) = self
+            .profiles
+            .iter_mut()
+            .find(|p| Some(&p.id) == self.active_profile.as_ref())
+        {
+            profile.shortcuts = shortcuts;
+        }
+    }
+}
+
+/// Environment variable that points Lens at a specific config file (tests, portable installs)
+pub const CONFIG_PATH_ENV: &str = "LENS_CONFIG_PATH";
+
+/// Which location the config file was resolved to
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum ConfigLocation {
+    /// Set by `LENS_CONFIG_PATH`
+    EnvOverride,
+    /// The platform app config dir (the normal case)
+    AppConfigDir,
+    /// Fallback next to the executable
+    ExecutableDir,
+    /// Last-resort fallback in the temp dir; may not survive a reboot
+    TempDir,
 }
 
+static FALLBACK_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Path of config.json, used for both loading and saving
+///
+/// Precedence: `LENS_CONFIG_PATH` (if its parent dir can be created), then the app config
+/// dir, then a `lens-config` dir next to the executable, then `lens-app` in the temp dir.
+/// The first time a fallback is used, a `config-fallback` event carries its path.
 pub fn get_config_path(app: &AppHandle) -> Option<PathBuf> {
-    app.path()
-        .app_config_dir()
-        .ok()
-        .map(|p| p.join("config.json"))
+    resolve_config_path(app).map(|(path, _)| path)
 }
 
-pub fn load_config(app: &AppHandle) -> AppConfig {
-    if let Some(config_path) = get_config_path(app) {
-        if config_path.exists() {
-            if let Ok(content) = fs::read_to_string(config_path) {
-                if let Ok(config) = serde_json::from_str(&content) {
-                    return config;
-                }
-            }
+fn resolve_config_path(app: &AppHandle) -> Option<(PathBuf, ConfigLocation)> {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+        match path.parent().map(fs::create_dir_all) {
+            None | Some(Ok(())) => return Some((path, ConfigLocation::EnvOverride)),
+            Some(Err(e)) => eprintln!("Ignoring {}={}: {}", CONFIG_PATH_ENV, path.display(), e),
+        }
+    }
+
+    if let Ok(dir) = app.path().app_config_dir() {
+        if fs::create_dir_all(&dir).is_ok() {
+            return Some((dir.join("config.json"), ConfigLocation::AppConfigDir));
         }
     }
-    AppConfig::default()
+
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("lens-config")));
+    let (dir, location) = exe_dir
+        .filter(|dir| fs::create_dir_all(dir).is_ok())
+        .map(|dir| (dir, ConfigLocation::ExecutableDir))
+        .or_else(|| {
+            let dir = std::env::temp_dir().join("lens-app");
+            fs::create_dir_all(&dir)
+                .ok()
+                .map(|_| (dir, ConfigLocation::TempDir))
+        })?;
+    let path = dir.join("config.json");
+
+    if !FALLBACK_WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "App config dir unavailable, using {} instead",
+            path.display()
+        );
+        let _ = app.emit("config-fallback", path.to_string_lossy());
+    }
+    Some((path, location))
+}
+
+/// Where the values in effect came from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum ConfigOrigin {
+    /// Read from the config file as-is (missing fields still take their defaults)
+    Disk,
+    /// No readable config file, so everything is a default
+    Defaults,
+    /// Read from the config file and upgraded from an older format
+    Migrated,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConfigSource {
+    pub origin: ConfigOrigin,
+    pub path: Option<String>,
+    pub location: Option<ConfigLocation>,
+}
+
+/// Where config.json's contents are read from and written to
+///
+/// The app always uses FileConfigStore; tests swap in MemoryConfigStore to exercise
+/// config logic such as migration without a filesystem or an AppHandle.
+pub trait ConfigStore: Send + Sync {
+    /// The stored config JSON, or None if nothing has been saved yet
+    fn read(&self) -> Option<String>;
+    fn write(&self, contents: &str) -> io::Result<()>;
+}
+
+/// config.json on disk, replaced atomically on every write
+pub struct FileConfigStore {
+    path: PathBuf,
+}
+
+impl FileConfigStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
 }
 
-pub fn save_config(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
-    if let Some(config_path) = get_config_path(app) {
-        if let Some(parent) = config_path.parent() {
+impl ConfigStore for FileConfigStore {
+    fn read(&self) -> Option<String> {
+        fs::read_to_string(&self.path).ok()
+    }
+
+    fn write(&self, contents: &str) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-        fs::write(config_path, content).map_err(|e| e.to_string())?;
+        write_atomic(&self.path, contents.as_bytes())
+    }
+}
+
+/// Config held in memory only, never touching disk
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryConfigStore {
+    contents: Mutex<Option<String>>,
+}
+
+#[cfg(test)]
+impl ConfigStore for MemoryConfigStore {
+    fn read(&self) -> Option<String> {
+        self.contents.lock().unwrap().clone()
+    }
+
+    fn write(&self, contents: &str) -> io::Result<()> {
+        *self.contents.lock().unwrap() = Some(contents.to_string());
         Ok(())
+    }
+}
+
+/// The config backend load_config and save_config go through
+///
+/// Empty by default, which means config.json at get_config_path; tests use set_store to
+/// swap in another backend.
+#[derive(Default)]
+pub struct ConfigStoreState(Mutex<Option<Arc<dyn ConfigStore>>>);
+
+#[cfg(test)]
+impl ConfigStoreState {
+    pub fn set_store(&self, store: Arc<dyn ConfigStore>) {
+        *self.0.lock().unwrap() = Some(store);
+    }
+}
+
+fn config_store(app: &AppHandle) -> Option<Arc<dyn ConfigStore>> {
+    let custom = app
+        .try_state::<ConfigStoreState>()
+        .and_then(|state| state.0.lock().unwrap().clone());
+    match custom {
+        Some(store) => Some(store),
+        None => Some(Arc::new(FileConfigStore::new(get_config_path(app)?))),
+    }
+}
+
+pub fn load_config(app: &AppHandle) -> AppConfig {
+    load_config_with_origin(app).0
+}
+
+fn load_config_with_origin(app: &AppHandle) -> (AppConfig, ConfigOrigin) {
+    match config_store(app) {
+        Some(store) => load_from(store.as_ref()),
+        None => defaults(),
+    }
+}
+
+fn defaults() -> (AppConfig, ConfigOrigin) {
+    let mut config = AppConfig::default();
+    config.migrate();
+    (config, ConfigOrigin::Defaults)
+}
+
+/// Read the config from `store`, upgrading older formats and filling in defaults
+pub fn load_from(store: &dyn ConfigStore) -> (AppConfig, ConfigOrigin) {
+    let Some(mut config) = read_from(store) else {
+        return defaults();
+    };
+
+    let origin = if config.migrate() {
+        ConfigOrigin::Migrated
     } else {
-        Err("Failed to get config path".to_string())
+        ConfigOrigin::Disk
+    };
+    (config, origin)
+}
+
+/// The fully resolved config the backend is using, after migration and default-filling
+#[tauri::command]
+pub fn get_effective_config(app: AppHandle) -> AppConfig {
+    load_config(&app)
+}
+
+/// JSON Schema for config.json, so editors can validate hand edits
+///
+/// Derived from the config structs, so it always matches what load_config accepts.
+#[tauri::command]
+pub fn get_config_schema() -> String {
+    let schema = schemars::schema_for!(AppConfig);
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+/// Whether the effective config came from disk, defaults or a migration, and which file
+#[tauri::command]
+pub fn get_config_source(app: AppHandle) -> ConfigSource {
+    let resolved = resolve_config_path(&app);
+    ConfigSource {
+        origin: load_config_with_origin(&app).1,
+        path: resolved
+            .as_ref()
+            .map(|(path, _)| path.to_string_lossy().into_owned()),
+        location: resolved.map(|(_, location)| location),
+    }
+}
+
+/// The stored config as-is, or None when it's missing or unparseable
+fn read_from(store: &dyn ConfigStore) -> Option<AppConfig> {
+    serde_json::from_str(&store.read()?).ok()
+}
+
+/// Held for the duration of each config write, so flush_config can wait one out
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Write the config to disk, recording what changed in the config history
+///
+/// `source` names what made the change, usually the command, and is logged with it.
+pub fn save_config(app: &AppHandle, config: &AppConfig, source: &str) -> Result<(), String> {
+    let _write = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let saved = config_store(app)
+        .ok_or_else(|| "Failed to get config path".to_string())
+        .and_then(|store| save_to(store.as_ref(), config));
+    let previous = last_errors::track(app, Subsystem::Config, saved)?;
+    crate::config_history::record(app, &previous, config, source);
+    Ok(())
+}
+
+/// Write the config to `store`, returning what was stored before
+pub fn save_to(store: &dyn ConfigStore, config: &AppConfig) -> Result<AppConfig, String> {
+    let previous = read_from(store).unwrap_or_default();
+    let content = if config.compact_json {
+        serde_json::to_string(config)
+    } else {
+        serde_json::to_string_pretty(config)
+    }
+    .map_err(|e| e.to_string())?;
+    store.write(&content).map_err(|e| e.to_string())?;
+    Ok(previous)
+}
+
+/// A config change waiting for its debounce to settle
+type DeferredEdit = Box<dyn FnOnce(&mut AppConfig) + Send>;
+
+/// Config changes held back by a debounce, keyed by what they update
+///
+/// A later change under the same key replaces the earlier one, so only the latest
+/// geometry of a drag is kept.
+#[derive(Default)]
+pub struct PendingSaveState(Mutex<HashMap<&'static str, DeferredEdit>>);
+
+/// Hold `edit` under `key` until save_deferred or flush_config writes it
+pub fn defer_save(
+    app: &AppHandle,
+    key: &'static str,
+    edit: impl FnOnce(&mut AppConfig) + Send + 'static,
+) {
+    app.state::<PendingSaveState>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(key, Box::new(edit));
+}
+
+/// Write the change deferred under `key`, if flush_config hasn't already
+pub fn save_deferred(app: &AppHandle, key: &'static str) -> Result<(), String> {
+    let edit = app
+        .state::<PendingSaveState>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(key);
+    match edit {
+        Some(edit) => save_edits(app, vec![edit], key),
+        None => Ok(()),
+    }
+}
+
+/// Drop the change deferred under `key` without writing it
+pub fn discard_deferred(app: &AppHandle, key: &'static str) {
+    app.state::<PendingSaveState>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(key);
+}
+
+fn save_edits(app: &AppHandle, edits: Vec<DeferredEdit>, source: &str) -> Result<(), String> {
+    let mut config = load_config(app);
+    for edit in edits {
+        edit(&mut config);
+    }
+    save_config(app, &config, source)
+}
+
+/// Write out every deferred save and wait for any config write in progress to reach disk
+///
+/// Debounced changes, such as the quick note's geometry or the size after a drag-resize,
+/// are written synchronously here instead of when their debounce settles. Call it before
+/// restarting for an update to be sure the last change was persisted.
+#[tauri::command]
+pub fn flush_config(app: AppHandle) -> Result<(), String> {
+    let edits: Vec<DeferredEdit> = app
+        .state::<PendingSaveState>()
+        .0
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, edit)| edit)
+        .collect();
+    if edits.is_empty() {
+        let _write = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        return Ok(());
+    }
+    save_edits(&app, edits, "flush_config")
+}
+
+/// Write via a temp file in the same directory and rename it over `path`
+///
+/// A process killed mid-write leaves at worst a stray temp file, never a truncated config.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        // Make sure the data is on disk before the rename makes it visible
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The combo pattern from the generated schema, as editors will apply it
+    fn combo_pattern() -> regex::Regex {
+        let schema: serde_json::Value = serde_json::from_str(&get_config_schema()).unwrap();
+        let toggle = &schema["definitions"]["ShortcutsConfig"]["properties"]["toggle"];
+        let pattern = toggle["anyOf"][0]["pattern"].as_str().unwrap();
+        regex::Regex::new(pattern).unwrap()
+    }
+
+    #[test]
+    fn schema_accepts_valid_combos() {
+        let pattern = combo_pattern();
+        for combo in [
+            "CommandOrControl+Shift+N",
+            "Alt+Space",
+            "Control+,",
+            "Control+/",
+            "Super+Shift+[",
+            "Control+\\",
+            "Shift+F12",
+            "F5",
+            "Alt+1",
+        ] {
+            assert!(pattern.is_match(combo), "{combo} should match");
+            assert!(
+                combo
+                    .parse::<tauri_plugin_global_shortcut::Shortcut>()
+                    .is_ok(),
+                "{combo} should parse"
+            );
+        }
+    }
+
+    #[test]
+    fn schema_rejects_malformed_combos() {
+        let pattern = combo_pattern();
+        for combo in [
+            "",
+            "Control+",
+            "+N",
+            "Control++",
+            "Control+Shift N",
+            "Control+,,",
+            "Control+é",
+        ] {
+            assert!(!pattern.is_match(combo), "{combo} should not match");
+        }
+    }
+
+    #[test]
+    fn empty_store_loads_defaults() {
+        let (config, origin) = load_from(&MemoryConfigStore::default());
+        assert_eq!(origin, ConfigOrigin::Defaults);
+        assert_eq!(config.active_profile.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn saved_config_round_trips() {
+        let store = MemoryConfigStore::default();
+        let (mut config, _) = load_from(&store);
+        config.muted = true;
+        config.corner_radius = 12.0;
+        config.auto_hide_after_ms = Some(5000);
+        save_to(&store, &config).unwrap();
+
+        let (loaded, origin) = load_from(&store);
+        assert_eq!(origin, ConfigOrigin::Disk);
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn save_returns_the_previous_config() {
+        let store = MemoryConfigStore::default();
+        let (mut config, _) = load_from(&store);
+        config.muted = true;
+        save_to(&store, &config).unwrap();
+
+        config.muted = false;
+        let previous = save_to(&store, &config).unwrap();
+        assert!(previous.muted);
+        assert!(!load_from(&store).0.muted);
+    }
+
+    #[test]
+    fn config_without_profiles_is_migrated() {
+        let store = MemoryConfigStore::default();
+        store.write(r#"{"muted": true}"#).unwrap();
+
+        let (config, origin) = load_from(&store);
+        assert_eq!(origin, ConfigOrigin::Migrated);
+        assert!(config.muted);
+        assert_eq!(config.profiles.len(), 1);
+    }
+
+    #[test]
+    fn auto_rotation_reads_as_none() {
+        let store = MemoryConfigStore::default();
+        store.write(r#"{"capture_rotation": "Auto"}"#).unwrap();
+        assert_eq!(load_from(&store).0.capture_rotation, CaptureRotation::None);
+    }
+
+    #[test]
+    fn set_store_replaces_the_file_backend() {
+        let state = ConfigStoreState::default();
+        assert!(state.0.lock().unwrap().is_none());
+
+        let store = Arc::new(MemoryConfigStore::default());
+        state.set_store(store.clone());
+        let active = state.0.lock().unwrap().clone().unwrap();
+        save_to(active.as_ref(), &AppConfig::default()).unwrap();
+        assert!(store.read().is_some());
+    }
+
+    #[test]
+    fn interrupted_write_keeps_the_old_config() {
+        let dir = std::env::temp_dir().join(format!("lens-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("config.json");
+        let store = FileConfigStore::new(path);
+        let (mut config, _) = load_from(&store);
+        config.muted = true;
+        save_to(&store, &config).unwrap();
+
+        // A write killed before its rename leaves only a partial temp file behind
+        fs::write(dir.join("config.json.tmp"), "{\"muted\": fal").unwrap();
+        let (loaded, origin) = load_from(&store);
+        assert_eq!(origin, ConfigOrigin::Disk);
+        assert!(loaded.muted);
+
+        // The next save replaces the stale temp file instead of tripping over it
+        config.muted = false;
+        save_to(&store, &config).unwrap();
+        assert!(!load_from(&store).0.muted);
+        assert!(!dir.join("config.json.tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unparseable_config_falls_back_to_defaults() {
+        let store = MemoryConfigStore::default();
+        store.write("{ not json").unwrap();
+        assert_eq!(load_from(&store).1, ConfigOrigin::Defaults);
     }
 }
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::config::{self, AppConfig};
+
+const HISTORY_FILE: &str = "config-history.jsonl";
+const ROTATED_HISTORY_FILE: &str = "config-history.1.jsonl";
+/// Size at which the log is rotated; one rotated file is kept, so at most twice this is used
+const MAX_HISTORY_BYTES: u64 = 64 * 1024;
+
+/// One changed config field, as recorded by save_config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigChange {
+    pub timestamp_ms: u64,
+    /// Dotted path of the field, e.g. "shortcuts.toggle"
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+    /// What made the change, e.g. the command that saved it
+    pub source: String,
+}
+
+fn history_paths(app: &AppHandle) -> Option<(PathBuf, PathBuf)> {
+    let dir = config::get_config_path(app)?.parent()?.to_path_buf();
+    Some((dir.join(HISTORY_FILE), dir.join(ROTATED_HISTORY_FILE)))
+}
+
+/// Append the fields that differ between `old` and `new` to the history log
+///
+/// Failures are only logged; the audit trail must never stop a config save.
+pub fn record(app: &AppHandle, old: &AppConfig, new: &AppConfig, source: &str) {
+    let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return;
+    };
+    let mut changes = Vec::new();
+    diff_values("", &old, &new, &mut changes);
+    if changes.is_empty() {
+        return;
+    }
+    let Some((path, rotated)) = history_paths(app) else {
+        return;
+    };
+
+    if fs::metadata(&path).map(|m| m.len()).unwrap_or(0) > MAX_HISTORY_BYTES {
+        let _ = fs::rename(&path, &rotated);
+    }
+
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut lines = String::new();
+    for (field, old, new) in changes {
+        let change = ConfigChange {
+            timestamp_ms,
+            field,
+            old,
+            new,
+            source: source.to_string(),
+        };
+        if let Ok(line) = serde_json::to_string(&change) {
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+    }
+
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("Failed to write config history: {}", e);
+    }
+}
+
+/// Collect (path, old, new) for every leaf that differs, recursing into objects
+fn diff_values(prefix: &str, old: &Value, new: &Value, out: &mut Vec<(String, Value, Value)>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            let mut keys: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                diff_values(
+                    &path,
+                    old_fields.get(key).unwrap_or(&Value::Null),
+                    new_fields.get(key).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        _ if old != new => out.push((prefix.to_string(), old.clone(), new.clone())),
+        _ => {}
+    }
+}
+
+/// Recent config changes, oldest first, to explain how settings reached their current state
+#[tauri::command]
+pub fn get_config_history(app: AppHandle) -> Vec<ConfigChange> {
+    let Some((path, rotated)) = history_paths(&app) else {
+        return Vec::new();
+    };
+    [rotated, path]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<ConfigChange>>()
+        })
+        .collect()
+}
+
+/// Delete the config change history
+#[tauri::command]
+pub fn clear_config_history(app: AppHandle) -> Result<(), String> {
+    let (path, rotated) = history_paths(&app).ok_or("Failed to get config path")?;
+    for path in [path, rotated] {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
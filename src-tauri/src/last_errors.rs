@@ -0,0 +1,75 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// Parts of the app whose failures are remembered for get_last_errors
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Shortcuts,
+    Capture,
+    ContentProtection,
+    Config,
+}
+
+/// The most recent failure in a subsystem
+#[derive(Debug, Serialize, Clone)]
+pub struct LastError {
+    pub subsystem: Subsystem,
+    pub message: String,
+    pub timestamp_ms: u64,
+}
+
+/// Latest unresolved error per subsystem; an entry is cleared by the next success there
+#[derive(Default)]
+pub struct LastErrors(Mutex<HashMap<Subsystem, LastError>>);
+
+/// Remember `message` as the latest error in `subsystem`
+pub fn record_error(app: &AppHandle, subsystem: Subsystem, message: impl Into<String>) {
+    let Some(state) = app.try_state::<LastErrors>() else {
+        return;
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    state.0.lock().unwrap().insert(
+        subsystem,
+        LastError {
+            subsystem,
+            message: message.into(),
+            timestamp_ms,
+        },
+    );
+}
+
+/// Forget the last error in `subsystem` after it succeeds
+pub fn clear_error(app: &AppHandle, subsystem: Subsystem) {
+    if let Some(state) = app.try_state::<LastErrors>() {
+        state.0.lock().unwrap().remove(&subsystem);
+    }
+}
+
+/// Record or clear `subsystem`'s last error from `result`, passing the result through
+pub fn track<T>(
+    app: &AppHandle,
+    subsystem: Subsystem,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    match &result {
+        Ok(_) => clear_error(app, subsystem),
+        Err(e) => record_error(app, subsystem, e.as_str()),
+    }
+    result
+}
+
+/// The latest error in each subsystem that hasn't succeeded since, oldest first
+///
+/// Covers failures that are otherwise only logged, like a shortcut the OS refused.
+#[tauri::command]
+pub fn get_last_errors(state: tauri::State<'_, LastErrors>) -> Vec<LastError> {
+    let mut errors: Vec<LastError> = state.0.lock().unwrap().values().cloned().collect();
+    errors.sort_by_key(|e| e.timestamp_ms);
+    errors
+}
@@ -1,12 +1,41 @@
+mod automation;
+mod background;
 mod capture;
 mod commands;
 mod config;
+mod config_history;
+mod last_errors;
+mod monitors;
+mod power;
 
 use tauri::{Emitter, Manager};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 
+use automation::AutomationServerState;
+use background::BackgroundState;
+use capture::binary::BinaryCaptureState;
 use capture::capture_screen;
-use commands::{resize_window, set_content_protection, toggle_window};
+use capture::countdown::CountdownState;
+use capture::diff::CaptureDiffState;
+use capture::stream::CaptureStreamState;
+use capture::watch::ScreenWatchState;
+use commands::focus::{InputFocusState, ShowFocusState};
+use commands::hot_corner::HotCornerState;
+use commands::idle::IdleState;
+use commands::peek::PeekState;
+use commands::quick_note::QuickNoteState;
+use commands::region::RegionSelectionState;
+use commands::shortcuts::{
+    ActionCooldownState, HeldShortcutsState, ShortcutBinding, TestShortcutState,
+};
+use commands::window::{ContentProtectionState, ReloadState, ResizeState};
+use commands::{
+    apply_window_settings, resize_window, set_content_protection, set_overlay_level,
+    toggle_quick_note, toggle_window,
+};
+use config::{ConfigStoreState, PendingSaveState};
+use last_errors::LastErrors;
+use power::KeepAwakeState;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -16,31 +45,75 @@ fn greet(name: &str) -> String {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(ConfigStoreState::default())
+        .manage(PendingSaveState::default())
+        .manage(LastErrors::default())
+        .manage(ContentProtectionState::default())
+        .manage(ResizeState::default())
+        .manage(ReloadState::default())
+        .on_page_load(commands::window::handle_page_load)
+        .manage(CaptureStreamState::default())
+        .manage(CountdownState::default())
+        .manage(InputFocusState::default())
+        .manage(ShowFocusState::default())
+        .manage(IdleState::default())
+        .manage(HotCornerState::default())
+        .manage(QuickNoteState::default())
+        .manage(RegionSelectionState::default())
+        .manage(PeekState::default())
+        .manage(TestShortcutState::default())
+        .manage(HeldShortcutsState::default())
+        .manage(ActionCooldownState::default())
+        .manage(CaptureDiffState::default())
+        .manage(ScreenWatchState::default())
+        .manage(AutomationServerState::default())
+        .manage(KeepAwakeState::default())
+        .manage(BackgroundState::default())
+        .manage(BinaryCaptureState::default())
+        .register_uri_scheme_protocol(
+            capture::binary::CAPTURE_SCHEME,
+            capture::binary::handle_protocol,
+        )
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler(|app, shortcut, event| {
+                    if commands::shortcuts::is_key_repeat(app, shortcut, event.state) {
+                        return;
+                    }
+                    if commands::peek::handle_shortcut(app, shortcut, event.state) {
+                        return;
+                    }
                     if event.state == ShortcutState::Pressed {
+                        if commands::region::handle_shortcut(app, shortcut) {
+                            return;
+                        }
+                        if commands::shortcuts::handle_test_shortcut(app, shortcut) {
+                            return;
+                        }
                         let config = config::load_config(app);
-                        let toggle_shortcut: tauri_plugin_global_shortcut::Shortcut =
-                            config.shortcuts.toggle.parse().unwrap();
-                        let ask_shortcut: tauri_plugin_global_shortcut::Shortcut =
-                            config.shortcuts.ask.parse().unwrap();
-                        let screen_share_shortcut: tauri_plugin_global_shortcut::Shortcut =
-                            config.shortcuts.screen_share.parse().unwrap();
-
-                        if shortcut == &toggle_shortcut {
-                            let _ = app.emit("toggle-window-triggered", ());
-                        } else if shortcut == &ask_shortcut {
-                            // Emit ask event (shortcut is only registered when window is visible)
-                            if let Some(window) = app.get_webview_window("main") {
-                                let is_focused = window.is_focused().unwrap_or(false);
-                                let _ = window.set_focus();
-                                let _ = app.emit("ask-triggered", !is_focused);
+                        match commands::shortcuts::binding_for(&config, shortcut) {
+                            Some(ShortcutBinding::Capture(target)) => {
+                                if let Err(e) = commands::shortcuts::run_with_cooldown(
+                                    app,
+                                    "screen_share",
+                                    || commands::shortcuts::run_screen_share(app, target),
+                                ) {
+                                    eprintln!("Failed to run screen_share action: {}", e);
+                                }
+                            }
+                            Some(ShortcutBinding::Action(action)) => {
+                                if let Err(e) =
+                                    commands::shortcuts::run_with_cooldown(app, action, || {
+                                        commands::shortcuts::run_action(app, action)
+                                    })
+                                {
+                                    eprintln!("Failed to run {} action: {}", action, e);
+                                }
                             }
-                        } else if shortcut == &screen_share_shortcut {
-                            let _ = app.emit("screen-share-triggered", ());
+                            None => {}
                         }
                     }
                 })
@@ -53,24 +126,48 @@ pub fn run() {
                     .plugin(tauri_plugin_updater::Builder::new().build())?;
                 // Register shortcuts from config
                 let config = config::load_config(app.handle());
+                commands::shortcuts::log_invalid_combos(&config);
                 let global_shortcut = app.handle().global_shortcut();
 
-                // Helper to safely register shortcuts
-                let register_shortcut =
+                // Helper to safely register shortcuts, collecting failures for get_last_errors
+                let mut errors = Vec::new();
+                let mut register_shortcut =
                     |shortcut_str: &str| match shortcut_str
                         .parse::<tauri_plugin_global_shortcut::Shortcut>()
                     {
                         Ok(shortcut) => {
                             if let Err(e) = global_shortcut.register(shortcut) {
                                 eprintln!("Failed to register shortcut {}: {}", shortcut_str, e);
+                                errors.push(format!("{}: {}", shortcut_str, e));
                             }
                         }
-                        Err(e) => eprintln!("Failed to parse shortcut {}: {}", shortcut_str, e),
+                        // Already logged by log_invalid_combos
+                        Err(e) => errors.push(format!("{}: {}", shortcut_str, e)),
                     };
 
-                register_shortcut(&config.shortcuts.toggle);
-                register_shortcut(&config.shortcuts.ask);
-                register_shortcut(&config.shortcuts.screen_share);
+                for (action, combos) in config.shortcuts.bindings() {
+                    // Optional actions wait for the first toggle when launching hidden
+                    if config.start_hidden
+                        && !commands::shortcuts::always_registered(&config, action)
+                    {
+                        continue;
+                    }
+                    for combo in combos {
+                        register_shortcut(combo);
+                    }
+                }
+                if let Some(combo) = &config.peek_shortcut {
+                    register_shortcut(combo);
+                }
+                if !config.start_hidden {
+                    for (shortcut, _) in commands::shortcuts::capture_variants(&config) {
+                        if let Err(e) = global_shortcut.register(shortcut) {
+                            eprintln!("Failed to register shortcut {}: {}", shortcut, e);
+                            errors.push(format!("{}: {}", shortcut, e));
+                        }
+                    }
+                }
+                commands::shortcuts::track_registration_errors(app.handle(), errors);
 
                 // System Tray Setup
                 use tauri::menu::{Menu, MenuItem};
@@ -81,18 +178,21 @@ pub fn run() {
                 let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
                 let menu = Menu::with_items(app, &[&toggle_item, &quit_item])?;
 
-                let toggle_shortcut = config.shortcuts.toggle.clone();
+                let toggle_shortcut = config.shortcuts.toggle.join(" or ");
 
-                TrayIconBuilder::new()
-                    .icon(app.default_window_icon().unwrap().clone())
-                    .menu(&menu)
+                let mut tray = TrayIconBuilder::with_id(commands::TRAY_ID);
+                if let Some(icon) = commands::tray::startup_icon(app.handle(), &config) {
+                    tray = tray.icon(icon);
+                }
+                tray.menu(&menu)
                     .tooltip(format!("Lens App - {} to toggle", toggle_shortcut))
                     .on_menu_event(move |_app, event| match event.id().as_ref() {
                         "toggle" => {
                             let _ = _app.emit("toggle-window-triggered", ());
                         }
                         "quit" => {
-                            std::process::exit(0);
+                            // Through the event loop, so RunEvent::Exit teardown still runs
+                            _app.exit(0);
                         }
                         _ => {}
                     })
@@ -111,19 +211,14 @@ pub fn run() {
 
             #[cfg(target_os = "macos")]
             {
-                use cocoa::appkit::{
-                    NSApplication, NSApplicationActivationPolicy, NSWindow,
-                    NSWindowCollectionBehavior,
-                };
-                use cocoa::base::{id, nil};
-
-                unsafe {
-                    // Set app as accessory - no Dock icon, no Cmd+Tab, but can still take focus
-                    let ns_app = NSApplication::sharedApplication(nil);
-                    ns_app.setActivationPolicy_(
-                        NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory,
-                    );
-                }
+                use cocoa::appkit::{NSWindow, NSWindowCollectionBehavior};
+                use cocoa::base::id;
+                use tauri::Manager;
+
+                let config = config::load_config(app.handle());
+
+                // Accessory by default - no Dock icon, no Cmd+Tab, but can still take focus
+                commands::window::apply_activation_policy(config.macos_activation_policy);
 
                 if let Some(window) = app.get_webview_window("main") {
                     unsafe {
@@ -140,6 +235,32 @@ pub fn run() {
                         ns_window.setCollectionBehavior_(collection_behavior);
                     }
                 }
+
+                if let Err(e) = commands::window::apply_overlay_level(
+                    app.handle(),
+                    config.overlay_above_fullscreen,
+                    config.window.always_on_top,
+                ) {
+                    eprintln!("Failed to apply overlay level: {}", e);
+                }
+                if let Err(e) =
+                    commands::window::apply_window_shadow(app.handle(), config.window_shadow)
+                {
+                    eprintln!("Failed to apply window shadow: {}", e);
+                }
+                if let Err(e) =
+                    commands::window::apply_corner_radius(app.handle(), config.corner_radius)
+                {
+                    eprintln!("Failed to apply corner radius: {}", e);
+                }
+                if config.window_vibrancy.is_some() {
+                    if let Err(e) = commands::window::apply_window_vibrancy(
+                        app.handle(),
+                        config.window_vibrancy,
+                    ) {
+                        eprintln!("Failed to apply window vibrancy: {}", e);
+                    }
+                }
             }
 
             #[cfg(target_os = "windows")]
@@ -147,6 +268,51 @@ pub fn run() {
                 // Windows specific setup if any left
             }
 
+            let config = config::load_config(app.handle());
+            if config.compact_mode.enabled {
+                if let Err(e) = resize_window(
+                    app.handle().clone(),
+                    config.compact_mode.width,
+                    config.compact_mode.height,
+                ) {
+                    eprintln!("Failed to restore compact mode size: {}", e);
+                }
+            } else if let (Some(width), Some(height)) =
+                (config.window_state.width, config.window_state.height)
+            {
+                // Restore a size saved by a drag-resize
+                if let Err(e) = resize_window(app.handle().clone(), width, height) {
+                    eprintln!("Failed to restore window size: {}", e);
+                }
+            }
+
+            // Restore a position saved by set_window_position, kept on a connected display
+            if let (Some(x), Some(y)) = (config.window_state.x, config.window_state.y) {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+                }
+                monitors::ensure_window_on_screen(app.handle());
+            }
+
+            // The window starts hidden (tauri.conf.json) so start_hidden doesn't flash it
+            if !config.start_hidden {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                }
+            }
+
+            // Clear captures left behind if a previous run was killed mid-capture
+            std::thread::spawn(capture::sweep_temp_captures);
+
+            monitors::watch_display_changes(app.handle());
+            capture::detect::watch_screen_capture(app.handle());
+            power::watch_system_wake(app.handle());
+            commands::window::track_user_resize(app.handle());
+            commands::window::track_window_moves(app.handle());
+            commands::idle::start_idle_watcher(app.handle());
+            commands::hot_corner::start_hot_corner_watcher(app.handle());
+            automation::start_automation_server(app.handle());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -154,11 +320,121 @@ pub fn run() {
             toggle_window,
             resize_window,
             capture_screen,
+            capture::capture_region,
+            capture::capture_screen_excluding,
+            capture::binary::capture_screen_binary,
+            capture::binary::clear_binary_captures,
+            capture::upload::capture_and_upload,
+            capture::upload::set_upload_endpoint,
+            capture::capture_region_around,
+            capture::capture_active_monitor,
+            capture::capture_raw,
+            capture::set_macos_capture_backend,
+            capture::set_capture_memory_only,
+            capture::set_default_capture_monitor,
+            capture::diff::capture_diff,
+            capture::detect::is_screen_being_captured,
+            capture::profiles::capture_with_profile,
+            capture::profiles::list_capture_profiles,
+            capture::profiles::save_capture_profile,
+            capture::profiles::delete_capture_profile,
+            capture::watch::start_screen_watch,
+            capture::watch::stop_screen_watch,
+            capture::stable::capture_when_stable,
+            capture::clipboard::capture_from_clipboard,
+            capture::capture_and_emit,
+            capture::capture_multi,
+            capture::cleanup_temp_captures,
+            capture::countdown::capture_with_countdown,
+            capture::countdown::cancel_countdown,
+            capture::stream::start_capture_stream,
+            capture::stream::stop_capture_stream,
             set_content_protection,
+            commands::window::set_content_protection_for,
+            commands::window::is_content_protection_effective,
+            commands::window::set_window_shadow,
+            commands::window::set_corner_radius,
+            commands::window::set_window_vibrancy,
+            toggle_quick_note,
+            set_overlay_level,
+            apply_window_settings,
+            commands::window::start_window_resize,
+            commands::window::set_activation_policy,
+            commands::window::set_compact_mode,
+            config::get_effective_config,
+            config::get_config_source,
+            config::get_config_schema,
+            config::flush_config,
+            config_history::get_config_history,
+            config_history::clear_config_history,
+            last_errors::get_last_errors,
+            monitors::get_cursor_position,
+            monitors::get_monitors,
+            monitors::get_monitor_layout,
+            power::set_keep_awake,
+            power::get_keep_awake,
+            background::pause_background,
+            background::resume_background,
+            commands::window::get_window_scale_factor,
+            commands::window::set_start_hidden,
+            commands::window::reset_window_geometry,
+            commands::window::set_window_position,
+            commands::window::get_window_position,
+            commands::window::sync_window_state_to_config,
+            commands::window::reload_window,
+            commands::attention::request_attention,
+            commands::attention::cancel_attention,
+            commands::audio::get_muted,
+            commands::audio::set_muted,
+            commands::audio::list_audio_devices,
+            commands::notifications::send_test_notification,
+            commands::permissions::get_permissions,
+            commands::permissions::open_permission_settings,
+            commands::focus::focus_for_input,
+            commands::focus::release_input_focus,
+            commands::input::type_text,
+            commands::input::paste_text,
+            commands::region::begin_region_selection,
+            commands::region::complete_region_selection,
+            commands::region::cancel_region_selection,
+            commands::idle::reset_idle_timer,
+            commands::idle::set_auto_hide_after,
+            commands::idle::set_hide_on_blur,
+            commands::hot_corner::set_hot_corner,
+            commands::peek::set_peek_shortcut,
             commands::shortcuts::get_shortcuts,
+            commands::shortcuts::get_shortcut_tokens,
             commands::shortcuts::update_shortcut,
-            commands::shortcuts::set_shortcuts
+            commands::shortcuts::set_shortcuts,
+            commands::shortcuts::reregister_shortcuts,
+            commands::shortcuts::set_ask_shows_window,
+            commands::shortcuts::set_action_cooldown,
+            commands::shortcuts::check_shortcut_conflict,
+            commands::shortcuts::validate_shortcuts,
+            commands::shortcuts::trigger_action,
+            commands::shortcuts::try_register_shortcut,
+            commands::shortcuts::try_unregister_shortcut,
+            commands::storage::get_storage_info,
+            commands::storage::clear_logs,
+            commands::tray::set_tray_icon,
+            commands::tray::reset_tray_icon,
+            commands::updates::get_update_channel,
+            commands::updates::set_update_channel,
+            commands::updates::check_for_updates,
+            commands::updates::get_updater_info,
+            commands::profiles::list_profiles,
+            commands::profiles::save_profile,
+            commands::profiles::activate_profile,
+            commands::profiles::delete_profile
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Don't leave ghost hotkeys registered with the OS
+                commands::shortcuts::teardown_shortcuts(app);
+                automation::stop_automation_server(app);
+                power::release_keep_awake(app);
+            }
+        });
 }
@@ -0,0 +1,371 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Monitor, PhysicalPosition};
+
+use crate::capture::CaptureRect;
+
+/// A connected display, in physical pixels
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+impl MonitorInfo {
+    fn from_monitor(monitor: &Monitor, primary: Option<&Monitor>) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        let is_primary = primary
+            .map(|p| p.name() == monitor.name() && p.position() == position)
+            .unwrap_or(false);
+        Self {
+            name: monitor.name().cloned(),
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            scale_factor: monitor.scale_factor(),
+            is_primary,
+        }
+    }
+
+    /// Which side of this monitor `other` touches, if they share part of an edge
+    fn side_of(&self, other: &MonitorInfo) -> Option<Side> {
+        let (right, bottom) = (self.x + self.width as i32, self.y + self.height as i32);
+        let (other_right, other_bottom) =
+            (other.x + other.width as i32, other.y + other.height as i32);
+        let overlaps_vertically = other.y < bottom && other_bottom > self.y;
+        let overlaps_horizontally = other.x < right && other_right > self.x;
+        let touches = |a: i32, b: i32| (a - b).abs() <= ADJACENT_TOLERANCE;
+
+        if overlaps_vertically && touches(other_right, self.x) {
+            Some(Side::Left)
+        } else if overlaps_vertically && touches(other.x, right) {
+            Some(Side::Right)
+        } else if overlaps_horizontally && touches(other_bottom, self.y) {
+            Some(Side::Above)
+        } else if overlaps_horizontally && touches(other.y, bottom) {
+            Some(Side::Below)
+        } else {
+            None
+        }
+    }
+
+    fn intersects(&self, x: i32, y: i32, width: u32, height: u32) -> bool {
+        x < self.x + self.width as i32
+            && x + width as i32 > self.x
+            && y < self.y + self.height as i32
+            && y + height as i32 > self.y
+    }
+}
+
+/// Global cursor position in physical pixels
+#[derive(Debug, Serialize, Clone)]
+pub struct CursorPos {
+    pub x: i32,
+    pub y: i32,
+    /// Name of the monitor under the cursor
+    pub monitor_id: Option<String>,
+}
+
+/// Get the global cursor position, which the webview can't observe outside its own window
+#[tauri::command]
+pub fn get_cursor_position(app: AppHandle) -> Result<CursorPos, String> {
+    let position = app.cursor_position().map_err(|e| e.to_string())?;
+    let monitor_id = app
+        .monitor_from_point(position.x, position.y)
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    Ok(CursorPos {
+        x: position.x.round() as i32,
+        y: position.y.round() as i32,
+        monitor_id,
+    })
+}
+
+/// Enumerate the currently connected monitors
+pub fn list_monitors(app: &AppHandle) -> Vec<MonitorInfo> {
+    let primary = app.primary_monitor().ok().flatten();
+    app.available_monitors()
+        .map(|monitors| {
+            monitors
+                .iter()
+                .map(|m| MonitorInfo::from_monitor(m, primary.as_ref()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The connected monitors with their scale factors, for sizing the overlay per display
+#[tauri::command]
+pub fn get_monitors(app: AppHandle) -> Vec<MonitorInfo> {
+    list_monitors(&app)
+}
+
+/// Edges closer than this still count as touching, since mixed scale factors can leave
+/// rounding gaps between physical rects
+const ADJACENT_TOLERANCE: i32 = 2;
+
+/// Which side of a monitor a neighbouring one sits on
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+    Above,
+    Below,
+}
+
+/// A monitor that shares an edge with another, by index into MonitorLayout::monitors
+#[derive(Debug, Serialize, Clone)]
+pub struct Neighbor {
+    pub index: usize,
+    pub side: Side,
+}
+
+/// A monitor in the layout, placed relative to the virtual desktop's top-left corner
+#[derive(Debug, Serialize, Clone)]
+pub struct LayoutMonitor {
+    pub monitor: MonitorInfo,
+    pub offset_x: u32,
+    pub offset_y: u32,
+    pub neighbors: Vec<Neighbor>,
+}
+
+/// The monitor arrangement, for drawing it to scale
+///
+/// Everything is in physical pixels. Global coordinates have their origin at the primary
+/// monitor's top-left corner, so monitors left of or above it have negative x or y; the
+/// offsets are from the top-left of `bounds` instead and never negative.
+#[derive(Debug, Serialize, Clone)]
+pub struct MonitorLayout {
+    pub monitors: Vec<LayoutMonitor>,
+    /// The virtual desktop: the smallest rect containing every monitor
+    pub bounds: CaptureRect,
+}
+
+/// Get the monitor arrangement with each monitor's neighbours and the virtual desktop bounds
+#[tauri::command]
+pub fn get_monitor_layout(app: AppHandle) -> MonitorLayout {
+    let monitors = list_monitors(&app);
+
+    let left = monitors.iter().map(|m| m.x).min().unwrap_or(0);
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let right = monitors
+        .iter()
+        .map(|m| m.x + m.width as i32)
+        .max()
+        .unwrap_or(0);
+    let bottom = monitors
+        .iter()
+        .map(|m| m.y + m.height as i32)
+        .max()
+        .unwrap_or(0);
+
+    let layout = monitors
+        .iter()
+        .map(|monitor| LayoutMonitor {
+            monitor: monitor.clone(),
+            offset_x: (monitor.x - left) as u32,
+            offset_y: (monitor.y - top) as u32,
+            neighbors: monitors
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| *other != monitor)
+                .filter_map(|(index, other)| {
+                    monitor.side_of(other).map(|side| Neighbor { index, side })
+                })
+                .collect(),
+        })
+        .collect();
+
+    MonitorLayout {
+        monitors: layout,
+        bounds: CaptureRect {
+            x: left,
+            y: top,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        },
+    }
+}
+
+/// Move the main window back onto the primary monitor if it no longer overlaps any display
+pub fn ensure_window_on_screen(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+
+    let monitors = list_monitors(app);
+    if monitors
+        .iter()
+        .any(|m| m.intersects(position.x, position.y, size.width, size.height))
+    {
+        return;
+    }
+
+    let Some(target) = monitors
+        .iter()
+        .find(|m| m.is_primary)
+        .or_else(|| monitors.first())
+    else {
+        return;
+    };
+
+    let x = target.x + (target.width as i32 - size.width as i32).max(0) / 2;
+    let y = target.y + (target.height as i32 - size.height as i32).max(0) / 2;
+    let _ = window.set_position(PhysicalPosition::new(x, y));
+}
+
+/// Pull a window rect at (x, y) fully onto the monitor it overlaps most, or the nearest one
+///
+/// Returns the clamped position and that monitor, or None when no monitors are connected.
+pub fn clamp_to_monitor(
+    app: &AppHandle,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<(i32, i32, MonitorInfo)> {
+    let overlap = |m: &MonitorInfo| {
+        let w = (x + width as i32).min(m.x + m.width as i32) - x.max(m.x);
+        let h = (y + height as i32).min(m.y + m.height as i32) - y.max(m.y);
+        w.max(0) as i64 * h.max(0) as i64
+    };
+    let distance = |m: &MonitorInfo| {
+        let dx = (m.x + m.width as i32 / 2 - (x + width as i32 / 2)) as i64;
+        let dy = (m.y + m.height as i32 / 2 - (y + height as i32 / 2)) as i64;
+        dx * dx + dy * dy
+    };
+
+    let monitors = list_monitors(app);
+    let target = monitors
+        .iter()
+        .filter(|m| m.intersects(x, y, width, height))
+        .max_by_key(|m| overlap(m))
+        .or_else(|| monitors.iter().min_by_key(|m| distance(m)))?
+        .clone();
+
+    let x = x.clamp(
+        target.x,
+        target.x + (target.width as i32 - width as i32).max(0),
+    );
+    let y = y.clamp(
+        target.y,
+        target.y + (target.height as i32 - height as i32).max(0),
+    );
+    Some((x, y, target))
+}
+
+/// React to a change in the display layout (dock/undock, resolution change)
+fn handle_display_change(app: &AppHandle) {
+    crate::capture::buffers::clear();
+    let _ = app.emit("monitors-changed", list_monitors(app));
+    ensure_window_on_screen(app);
+}
+
+/// Start listening for OS display-configuration changes
+pub fn watch_display_changes(app: &AppHandle) {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+        use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+        use windows::Win32::UI::WindowsAndMessaging::WM_DISPLAYCHANGE;
+
+        unsafe extern "system" fn subclass_proc(
+            hwnd: HWND,
+            msg: u32,
+            wparam: WPARAM,
+            lparam: LPARAM,
+            _id: usize,
+            data: usize,
+        ) -> LRESULT {
+            if msg == WM_DISPLAYCHANGE {
+                let app = &*(data as *const AppHandle);
+                let app = app.clone();
+                // Handle off the window procedure so window APIs don't re-enter the event loop
+                std::thread::spawn(move || handle_display_change(&app));
+            }
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+
+        if let Some(window) = app.get_webview_window("main") {
+            if let Ok(hwnd) = window.hwnd() {
+                // Leaked intentionally: the subclass lives as long as the main window
+                let data = Box::into_raw(Box::new(app.clone())) as usize;
+                unsafe {
+                    let _ = SetWindowSubclass(HWND(hwnd.0), Some(subclass_proc), 1, data);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::NSString;
+        use objc::declare::ClassDecl;
+        use objc::runtime::{Object, Sel};
+        use objc::{class, msg_send, sel, sel_impl};
+        use std::sync::OnceLock;
+
+        static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+        extern "C" fn screen_parameters_changed(_this: &Object, _cmd: Sel, _notification: id) {
+            if let Some(app) = APP_HANDLE.get() {
+                let app = app.clone();
+                std::thread::spawn(move || handle_display_change(&app));
+            }
+        }
+
+        if APP_HANDLE.set(app.clone()).is_err() {
+            return;
+        }
+
+        unsafe {
+            let Some(mut decl) = ClassDecl::new("LensScreenObserver", class!(NSObject)) else {
+                return;
+            };
+            decl.add_method(
+                sel!(screenParametersChanged:),
+                screen_parameters_changed as extern "C" fn(&Object, Sel, id),
+            );
+            let observer_class = decl.register();
+
+            let observer: id = msg_send![observer_class, new];
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let name =
+                NSString::alloc(nil).init_str("NSApplicationDidChangeScreenParametersNotification");
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(screenParametersChanged:)
+                name: name
+                object: nil];
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        // No display-change notification is wired up here, so poll the monitor list instead
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let mut last = list_monitors(&app);
+            loop {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+                let current = list_monitors(&app);
+                if current != last {
+                    handle_display_change(&app);
+                    last = current;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,305 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::shortcuts::reregister_all_shortcuts;
+
+/// React to the machine waking from sleep
+fn handle_wake(app: &AppHandle) {
+    // The OS may have dropped our global hotkeys while asleep
+    for registration in reregister_all_shortcuts(app) {
+        if let Some(error) = registration.error {
+            eprintln!(
+                "Failed to re-register {} after wake: {}",
+                registration.action, error
+            );
+        }
+    }
+}
+
+/// Start listening for the system resuming from sleep
+pub fn watch_system_wake(app: &AppHandle) {
+    #[cfg(target_os = "windows")]
+    {
+        use tauri::Manager;
+        use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+        use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+        use windows::Win32::UI::WindowsAndMessaging::{PBT_APMRESUMEAUTOMATIC, WM_POWERBROADCAST};
+
+        unsafe extern "system" fn subclass_proc(
+            hwnd: HWND,
+            msg: u32,
+            wparam: WPARAM,
+            lparam: LPARAM,
+            _id: usize,
+            data: usize,
+        ) -> LRESULT {
+            if msg == WM_POWERBROADCAST && wparam.0 == PBT_APMRESUMEAUTOMATIC as usize {
+                let app = &*(data as *const AppHandle);
+                let app = app.clone();
+                std::thread::spawn(move || handle_wake(&app));
+            }
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+
+        if let Some(window) = app.get_webview_window("main") {
+            if let Ok(hwnd) = window.hwnd() {
+                // Leaked intentionally: the subclass lives as long as the main window
+                let data = Box::into_raw(Box::new(app.clone())) as usize;
+                unsafe {
+                    let _ = SetWindowSubclass(HWND(hwnd.0), Some(subclass_proc), 2, data);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::NSString;
+        use objc::declare::ClassDecl;
+        use objc::runtime::{Object, Sel};
+        use objc::{class, msg_send, sel, sel_impl};
+        use std::sync::OnceLock;
+
+        static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+        extern "C" fn did_wake(_this: &Object, _cmd: Sel, _notification: id) {
+            if let Some(app) = APP_HANDLE.get() {
+                let app = app.clone();
+                std::thread::spawn(move || handle_wake(&app));
+            }
+        }
+
+        if APP_HANDLE.set(app.clone()).is_err() {
+            return;
+        }
+
+        unsafe {
+            let Some(mut decl) = ClassDecl::new("LensWakeObserver", class!(NSObject)) else {
+                return;
+            };
+            decl.add_method(sel!(didWake:), did_wake as extern "C" fn(&Object, Sel, id));
+            let observer_class = decl.register();
+
+            let observer: id = msg_send![observer_class, new];
+            // Wake notifications are only posted to NSWorkspace's own notification center
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: id = msg_send![workspace, notificationCenter];
+            let name = NSString::alloc(nil).init_str("NSWorkspaceDidWakeNotification");
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(didWake:)
+                name: name
+                object: nil];
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        use std::time::{Duration, Instant, SystemTime};
+
+        // The monotonic clock stops during suspend while the wall clock keeps going, so a
+        // wall-clock jump well past the poll interval means the machine slept
+        const POLL_INTERVAL: Duration = Duration::from_secs(5);
+        const WAKE_THRESHOLD: Duration = Duration::from_secs(30);
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let mut last_wall = SystemTime::now();
+            let mut last_mono = Instant::now();
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let wall = SystemTime::now();
+                let mono = Instant::now();
+                let wall_elapsed = wall.duration_since(last_wall).unwrap_or_default();
+                if wall_elapsed > (mono - last_mono) + WAKE_THRESHOLD {
+                    handle_wake(&app);
+                }
+                last_wall = wall;
+                last_mono = mono;
+            }
+        });
+    }
+}
+
+/// Keeps the display awake while the user asks for it or a capture stream is running
+#[derive(Default)]
+pub struct KeepAwakeState(Mutex<KeepAwake>);
+
+#[derive(Default)]
+struct KeepAwake {
+    /// Set by set_keep_awake
+    requested: bool,
+    /// Capture streams currently holding the display awake
+    streams: u32,
+    assertion: Option<SleepAssertion>,
+}
+
+impl KeepAwake {
+    /// Take or drop the OS assertion to match what's wanted now
+    fn update(&mut self) -> Result<(), String> {
+        let wanted = self.requested || self.streams > 0;
+        match (wanted, self.assertion.is_some()) {
+            (true, false) => self.assertion = Some(SleepAssertion::acquire()?),
+            (false, true) => {
+                if let Some(assertion) = self.assertion.take() {
+                    assertion.release();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// An OS request to keep the display and system awake, held until released
+#[cfg(target_os = "macos")]
+struct SleepAssertion(u32);
+
+/// On Windows the execution state belongs to the thread that set it, so a dedicated thread
+/// sets it and holds it until the sender is dropped
+#[cfg(target_os = "windows")]
+struct SleepAssertion(std::sync::mpsc::Sender<()>);
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+struct SleepAssertion;
+
+impl SleepAssertion {
+    #[cfg(target_os = "macos")]
+    fn acquire() -> Result<Self, String> {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::NSString;
+        use std::ffi::c_void;
+
+        #[link(name = "IOKit", kind = "framework")]
+        extern "C" {
+            fn IOPMAssertionCreateWithName(
+                assertion_type: *const c_void,
+                level: u32,
+                name: *const c_void,
+                assertion_id: *mut u32,
+            ) -> i32;
+        }
+        // kIOPMAssertionLevelOn
+        const ASSERTION_LEVEL_ON: u32 = 255;
+
+        unsafe {
+            // NSString is toll-free bridged to the CFStringRef IOKit expects
+            let assertion_type: id = NSString::alloc(nil).init_str("PreventUserIdleDisplaySleep");
+            let name: id = NSString::alloc(nil).init_str("Lens keep awake");
+            let mut assertion_id = 0u32;
+            let status = IOPMAssertionCreateWithName(
+                assertion_type as *const c_void,
+                ASSERTION_LEVEL_ON,
+                name as *const c_void,
+                &mut assertion_id,
+            );
+            if status != 0 {
+                return Err(format!("IOPMAssertionCreateWithName failed: {}", status));
+            }
+            Ok(Self(assertion_id))
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn release(self) {
+        #[link(name = "IOKit", kind = "framework")]
+        extern "C" {
+            fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+        }
+        unsafe {
+            IOPMAssertionRelease(self.0);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn acquire() -> Result<Self, String> {
+        use windows::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let (started, result) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let previous = unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED)
+            };
+            let _ = started.send(previous.0 != 0);
+            // Blocks until the assertion is released (sender dropped)
+            let _ = released.recv();
+            unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS);
+            }
+        });
+        match result.recv() {
+            Ok(true) => Ok(Self(release)),
+            _ => Err("SetThreadExecutionState failed".to_string()),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn release(self) {
+        drop(self.0);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn acquire() -> Result<Self, String> {
+        Err("Keep awake is not supported on this platform".to_string())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn release(self) {}
+}
+
+/// Keep the display and system from sleeping until turned off again
+#[tauri::command]
+pub fn set_keep_awake(state: State<'_, KeepAwakeState>, enabled: bool) -> Result<(), String> {
+    let mut keep_awake = state.0.lock().unwrap();
+    let was_requested = keep_awake.requested;
+    keep_awake.requested = enabled;
+    keep_awake.update().inspect_err(|_| {
+        keep_awake.requested = was_requested;
+    })
+}
+
+/// Whether the user has asked to keep the display awake
+#[tauri::command]
+pub fn get_keep_awake(state: State<'_, KeepAwakeState>) -> bool {
+    state.0.lock().unwrap().requested
+}
+
+/// Hold the display awake for a capture stream; pair with end_stream_keep_awake
+pub fn begin_stream_keep_awake(app: &AppHandle) {
+    let state = app.state::<KeepAwakeState>();
+    let mut keep_awake = state.0.lock().unwrap();
+    keep_awake.streams += 1;
+    if let Err(e) = keep_awake.update() {
+        eprintln!("Failed to keep the display awake: {}", e);
+    }
+}
+
+/// Drop a capture stream's hold, restoring whatever the user had chosen
+pub fn end_stream_keep_awake(app: &AppHandle) {
+    let state = app.state::<KeepAwakeState>();
+    let mut keep_awake = state.0.lock().unwrap();
+    keep_awake.streams = keep_awake.streams.saturating_sub(1);
+    let _ = keep_awake.update();
+}
+
+/// Drop the user's keep-awake request for a pause, returning whether it was on
+pub fn pause_keep_awake(app: &AppHandle) -> bool {
+    let state = app.state::<KeepAwakeState>();
+    let mut keep_awake = state.0.lock().unwrap();
+    let was_requested = std::mem::take(&mut keep_awake.requested);
+    let _ = keep_awake.update();
+    was_requested
+}
+
+/// Release any keep-awake assertion; called on exit
+pub fn release_keep_awake(app: &AppHandle) {
+    let state = app.state::<KeepAwakeState>();
+    let mut keep_awake = state.0.lock().unwrap();
+    keep_awake.requested = false;
+    keep_awake.streams = 0;
+    let _ = keep_awake.update();
+}
@@ -90,8 +90,15 @@ function App() {
   // Fetch shortcuts on mount and listen for changes
   useEffect(() => {
     const fetchShortcuts = () => {
-      invoke<{ toggle: string; ask: string; screen_share: string }>("get_shortcuts")
-        .then(setShortcuts)
+      // Each action can have several combos; show the primary one
+      invoke<{ toggle: string[]; ask: string[]; screen_share: string[] }>("get_shortcuts")
+        .then((result) =>
+          setShortcuts({
+            toggle: result.toggle[0] ?? "",
+            ask: result.ask[0] ?? "",
+            screen_share: result.screen_share[0] ?? "",
+          })
+        )
         .catch(console.error);
     };
 
@@ -387,8 +394,8 @@ function App() {
 
   const captureScreenshot = async (): Promise<string | null> => {
     try {
-      const base64 = await invoke<string>("capture_screen");
-      return base64 ?? null;
+      const result = await invoke<{ data: string }>("capture_screen");
+      return result?.data ?? null;
     } catch (error) {
       console.error("Screen capture failed:", error);
       return null;
@@ -781,11 +788,29 @@ function App() {
     };
   }, []);
 
+  useEffect(() => {
+    // Keep the backend idle auto-hide timer from firing while the user is active
+    let lastReset = 0;
+    const handleActivity = () => {
+      const now = Date.now();
+      if (now - lastReset < 1000) return;
+      lastReset = now;
+      invoke("reset_idle_timer").catch(console.error);
+    };
+
+    window.addEventListener("keydown", handleActivity);
+    window.addEventListener("mousemove", handleActivity);
+    return () => {
+      window.removeEventListener("keydown", handleActivity);
+      window.removeEventListener("mousemove", handleActivity);
+    };
+  }, []);
+
   useEffect(() => {
     // Apply content protection setting on startup
     const enabled = storage.getContentProtection();
     if (enabled) {
-      invoke("set_content_protection", { enabled });
+      invoke("set_content_protection", { enabled }).catch(console.error);
     }
   }, []);
 
@@ -926,9 +951,14 @@ function App() {
 
         lastWindowSizeRef.current = { width, height };
 
-        void invoke("resize_window", { width, height }).catch((error) => {
-          console.error("Window resize failed:", error);
-        });
+        void invoke<{ width: number; height: number }>("resize_window", { width, height })
+          .then((applied) => {
+            // Track what the window really ended up at, in case it was clamped
+            lastWindowSizeRef.current = { width: applied.width, height: applied.height };
+          })
+          .catch((error) => {
+            console.error("Window resize failed:", error);
+          });
       };
 
       if (delay > 0) {
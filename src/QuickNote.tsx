@@ -0,0 +1,35 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import "./styles/index.css";
+import { storage } from "./services/storage";
+
+// The sticky note toggled by the quick_note shortcut; its text stays in localStorage,
+// while the backend persists the window's position and size
+function QuickNote() {
+  const [text, setText] = useState(() => storage.getQuickNote());
+
+  const handleChange = (value: string) => {
+    setText(value);
+    storage.setQuickNote(value);
+  };
+
+  return (
+    <div className="quick-note">
+      <div className="quick-note-drag-handle" data-tauri-drag-region />
+      <textarea
+        className="quick-note-text"
+        value={text}
+        placeholder="Quick note…"
+        autoFocus
+        onChange={(e) => handleChange(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Escape") {
+            invoke("toggle_quick_note").catch(console.error);
+          }
+        }}
+      />
+    </div>
+  );
+}
+
+export default QuickNote;
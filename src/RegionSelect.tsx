@@ -0,0 +1,84 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import "./styles/components/regionSelect.css";
+
+interface Point {
+  x: number;
+  y: number;
+}
+
+// Drags smaller than this (in CSS pixels) are treated as a stray click
+const MIN_SELECTION = 4;
+
+// One of these covers each monitor during region selection. The dragged rectangle is
+// sent to the backend in physical desktop coordinates, which captures it and emits the
+// result as `capture-ready`.
+function RegionSelect() {
+  const [start, setStart] = useState<Point | null>(null);
+  const [current, setCurrent] = useState<Point | null>(null);
+  const origin = useRef<Point>({ x: 0, y: 0 });
+
+  useEffect(() => {
+    getCurrentWindow()
+      .outerPosition()
+      .then((position) => {
+        origin.current = { x: position.x, y: position.y };
+      })
+      .catch(console.error);
+
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key === "Escape") {
+        invoke("cancel_region_selection").catch(console.error);
+      }
+    };
+    window.addEventListener("keydown", handleKeyDown);
+    return () => window.removeEventListener("keydown", handleKeyDown);
+  }, []);
+
+  const selection =
+    start && current
+      ? {
+          left: Math.min(start.x, current.x),
+          top: Math.min(start.y, current.y),
+          width: Math.abs(current.x - start.x),
+          height: Math.abs(current.y - start.y),
+        }
+      : null;
+
+  const handleMouseUp = () => {
+    setStart(null);
+    setCurrent(null);
+    if (!selection || selection.width < MIN_SELECTION || selection.height < MIN_SELECTION) {
+      return;
+    }
+
+    const scale = window.devicePixelRatio;
+    invoke("complete_region_selection", {
+      rect: {
+        x: Math.round(origin.current.x + selection.left * scale),
+        y: Math.round(origin.current.y + selection.top * scale),
+        width: Math.round(selection.width * scale),
+        height: Math.round(selection.height * scale),
+      },
+    }).catch(console.error);
+  };
+
+  return (
+    <div
+      className="region-select"
+      onMouseDown={(e) => {
+        setStart({ x: e.clientX, y: e.clientY });
+        setCurrent({ x: e.clientX, y: e.clientY });
+      }}
+      onMouseMove={(e) => {
+        if (start) setCurrent({ x: e.clientX, y: e.clientY });
+      }}
+      onMouseUp={handleMouseUp}
+    >
+      {selection && <div className="region-select-box" style={selection} />}
+    </div>
+  );
+}
+
+export default RegionSelect;
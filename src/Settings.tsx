@@ -5,17 +5,18 @@ import { storage } from "./services/storage";
 import { ShortcutRecorder } from "./components/ShortcutRecorder";
 import { useAutoUpdate } from "./hooks/useAutoUpdate";
 
+// Each action can have several combos; the recorder edits the primary (first) one
 interface Shortcuts {
-  toggle: string;
-  ask: string;
-  screen_share: string;
+  toggle: string[];
+  ask: string[];
+  screen_share: string[];
 }
 
 function Settings() {
   const [openaiKey, setOpenaiKey] = useState("");
   const [geminiKey, setGeminiKey] = useState("");
   const [contentProtection, setContentProtection] = useState(false);
-  const [shortcuts, setShortcuts] = useState<Shortcuts>({ toggle: '', ask: '', screen_share: '' });
+  const [shortcuts, setShortcuts] = useState<Shortcuts>({ toggle: [], ask: [], screen_share: [] });
   const [saved, setSaved] = useState(false);
 
   const {
@@ -37,10 +38,10 @@ function Settings() {
     if (storedGeminiKey) setGeminiKey(storedGeminiKey);
     if (storedContentProtection) {
       setContentProtection(true);
-      invoke("set_content_protection", { enabled: true });
+      invoke("set_content_protection", { enabled: true }).catch(console.error);
     } else {
       // If content protection was not stored as true, ensure it's off
-      invoke("set_content_protection", { enabled: false });
+      invoke("set_content_protection", { enabled: false }).catch(console.error);
     }
 
     invoke<Shortcuts>('get_shortcuts').then(setShortcuts).catch(console.error);
@@ -63,7 +64,7 @@ function Settings() {
 
     // 2. Save Content Protection
     storage.setContentProtection(contentProtection);
-    invoke("set_content_protection", { enabled: contentProtection });
+    invoke("set_content_protection", { enabled: contentProtection }).catch(console.error);
 
     // 3. Save Shortcuts
     try {
@@ -82,7 +83,7 @@ function Settings() {
     // Basic conflict check in frontend
     const isConflict = Object.entries(shortcuts).some(([key, val]) => {
       if (key === action) return false;
-      return val === newValue;
+      return val.includes(newValue);
     });
 
     if (isConflict) {
@@ -90,7 +91,10 @@ function Settings() {
       return false;
     }
 
-    setShortcuts(prev => ({ ...prev, [action]: newValue }));
+    setShortcuts(prev => ({
+      ...prev,
+      [action]: [newValue, ...prev[action as keyof Shortcuts].slice(1)],
+    }));
     return true;
   };
 
@@ -122,21 +126,21 @@ function Settings() {
           <div className="shortcut-item">
             <span>Toggle Window</span>
             <ShortcutRecorder
-              value={shortcuts.toggle}
+              value={shortcuts.toggle[0] ?? ''}
               onChange={(val) => handleShortcutChange('toggle', val)}
             />
           </div>
           <div className="shortcut-item">
             <span>Ask AI</span>
             <ShortcutRecorder
-              value={shortcuts.ask}
+              value={shortcuts.ask[0] ?? ''}
               onChange={(val) => handleShortcutChange('ask', val)}
             />
           </div>
           <div className="shortcut-item">
             <span>Screen Share</span>
             <ShortcutRecorder
-              value={shortcuts.screen_share}
+              value={shortcuts.screen_share[0] ?? ''}
               onChange={(val) => handleShortcutChange('screen_share', val)}
             />
           </div>
@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
-import { check, Update } from '@tauri-apps/plugin-updater';
+import { invoke } from '@tauri-apps/api/core';
+import { Update } from '@tauri-apps/plugin-updater';
 import { relaunch } from '@tauri-apps/plugin-process';
 
 export interface UpdateInfo {
@@ -32,7 +33,9 @@ export function useAutoUpdate(checkOnMount = true, checkIntervalMs = 3600000) {
     setState(prev => ({ ...prev, checking: true, error: null }));
 
     try {
-      const result = await check();
+      // Checked in Rust so the configured release channel picks the manifest
+      const metadata = await invoke<ConstructorParameters<typeof Update>[0] | null>('check_for_updates');
+      const result = metadata ? new Update(metadata) : null;
 
       if (result) {
         setUpdate(result);
@@ -183,9 +183,14 @@ export function useWindow(options: UseWindowOptions): UseWindowReturn {
 
         lastWindowSizeRef.current = { width, height };
 
-        void invoke("resize_window", { width, height }).catch((error) => {
-          console.error("Window resize failed:", error);
-        });
+        void invoke<{ width: number; height: number }>("resize_window", { width, height })
+          .then((applied) => {
+            // Track what the window really ended up at, in case it was clamped
+            lastWindowSizeRef.current = { width: applied.width, height: applied.height };
+          })
+          .catch((error) => {
+            console.error("Window resize failed:", error);
+          });
       };
 
       if (delay > 0) {
@@ -6,6 +6,7 @@ const KEYS = {
   OPENAI_API_KEY: "openai_api_key",
   GEMINI_API_KEY: "gemini_api_key",
   CONTENT_PROTECTION: "content_protection",
+  QUICK_NOTE: "quick_note",
 } as const;
 
 export const storage = {
@@ -22,4 +23,8 @@ export const storage = {
   // Content Protection
   getContentProtection: (): boolean => localStorage.getItem(KEYS.CONTENT_PROTECTION) === "true",
   setContentProtection: (enabled: boolean): void => localStorage.setItem(KEYS.CONTENT_PROTECTION, String(enabled)),
+
+  // Quick Note
+  getQuickNote: (): string => localStorage.getItem(KEYS.QUICK_NOTE) ?? "",
+  setQuickNote: (text: string): void => localStorage.setItem(KEYS.QUICK_NOTE, text),
 };
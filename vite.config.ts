@@ -13,6 +13,8 @@ export default defineConfig(async () => ({
       input: {
         main: "index.html",
         settings: "settings.html",
+        quicknote: "quicknote.html",
+        regionselect: "regionselect.html",
       },
     },
   },